@@ -0,0 +1,163 @@
+use crate::byte_converter::LOG;
+use crate::feed::Push;
+use crate::logs::Logger;
+use crate::ring_buffer::RingBuffer;
+use crate::sums::ExactSums;
+
+// splits leaving fewer newest values are not tested, so few spikes can`t cut the window. Older part can be
+// shorter, leftovers of the old level are then dropped by later pushes.
+const MIN_SUB_WINDOW: usize = 5;
+
+// Adaptive window (ADWIN). Window grows while the stream is stationary and the older part is dropped as soon as
+// means of some older and newer part differ more than the bound for confidence delta (Bernstein bound with variance
+// of the window). The bound is not scale free: its variance term scales with the values, but the 2 / 3m * ln term
+// assumes a unit range, so on streams with spread of a few units it dominates and small shifts need longer sub
+// windows, while on large values it is negligible. Up to window_size values are kept, then the oldest is evicted
+// as in other rolling windows. Every push tests all splits, so push is O(window length).
+pub struct AdaptiveWindow<const N: usize> {
+    window: RingBuffer<i32, N>,
+    delta: f64,
+    sums: ExactSums,
+    // how many times older part of the window was dropped
+    shrinks: u64,
+}
+
+impl<const N: usize> AdaptiveWindow<N> {
+    // smaller delta means fewer false alarms and slower reaction, 0.002 is common
+    pub fn new(window_size: usize, delta: f32) -> Self {
+        let delta = if delta > 0.0 && delta < 1.0 {
+            delta
+        } else {
+            LOG.warn("delta has to be in (0, 1) range. Using 0.002");
+            0.002
+        };
+        Self {
+            window: RingBuffer::new(window_size),
+            delta: delta as f64,
+            sums: ExactSums::new(),
+            shrinks: 0,
+        }
+    }
+
+    // push value and drop the older part of the window while it differs from the newer one,
+    // returns true when the window was shrunk
+    pub fn push_value(&mut self, value: i32) -> bool {
+        if let Some(evicted) = self.window.push(value) {
+            self.sums.remove(evicted);
+        }
+        self.sums.add(value);
+
+        let mut shrunk = false;
+        while let Some(split) = self.find_cut() {
+            for _ in 0..split {
+                if let Some(old) = self.window.pop_oldest() {
+                    self.sums.remove(old);
+                }
+            }
+            shrunk = true;
+        }
+        if shrunk {
+            self.shrinks += 1;
+        }
+        shrunk
+    }
+
+    // length of the oldest part which differs from the rest the most over the bound, None when the window is
+    // consistent. Split with the largest excess is closest to the change, so old level is dropped at once.
+    fn find_cut(&self) -> Option<usize> {
+        let n = self.window.len();
+        if n <= MIN_SUB_WINDOW {
+            return None;
+        }
+        let variance = self.variance() as f64;
+        let ln_bound = (2.0 * n as f64 / self.delta).ln();
+        let mut older_sum = 0i64;
+        let mut cut: Option<(usize, f64)> = None;
+        for (i, value) in self.window.iter().take(n - MIN_SUB_WINDOW).enumerate() {
+            older_sum += *value as i64;
+            let n0 = i + 1;
+            let n1 = n - n0;
+            let older_mean = older_sum as f64 / n0 as f64;
+            let newer_mean = (self.sums.sum() - older_sum) as f64 / n1 as f64;
+            // harmonic mean of the sub window sizes
+            let m = 1.0 / (1.0 / n0 as f64 + 1.0 / n1 as f64);
+            let epsilon = (2.0 / m * variance * ln_bound).sqrt() + 2.0 / (3.0 * m) * ln_bound;
+            let excess = (older_mean - newer_mean).abs() - epsilon;
+            if excess > 0.0 && cut.is_none_or(|(_, best)| excess > best) {
+                cut = Some((n0, excess));
+            }
+        }
+        cut.map(|(n0, _)| n0)
+    }
+
+    // current length of the adaptive window
+    pub fn len(&self) -> usize {
+        self.window.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.window.is_empty()
+    }
+
+    // maximum length of the window
+    pub fn capacity(&self) -> usize {
+        self.window.window_size()
+    }
+
+    pub fn shrinks(&self) -> u64 {
+        self.shrinks
+    }
+
+    pub fn mean(&self) -> f32 {
+        self.sums.mean().unwrap_or(0.0) as f32
+    }
+
+    // population variance of the adaptive window
+    pub fn variance(&self) -> f32 {
+        self.sums.population_variance().unwrap_or(0.0) as f32
+    }
+
+    pub fn std_deviation(&self) -> f32 {
+        self.variance().sqrt()
+    }
+}
+
+impl<const N: usize> Push for AdaptiveWindow<N> {
+    fn push(&mut self, value: i32) {
+        self.push_value(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grows_while_stationary() {
+        let mut window = AdaptiveWindow::<64>::new(64, 0.002);
+        for i in 0..50 {
+            assert!(!window.push_value(100 + i % 3));
+        }
+        assert_eq!(window.len(), 50);
+        assert_eq!(window.shrinks(), 0);
+        // capacity bounds the memory
+        for i in 0..50 {
+            window.push_value(100 + i % 3);
+        }
+        assert_eq!(window.len(), window.capacity());
+    }
+
+    #[test]
+    fn test_drops_old_level() {
+        let mut window = AdaptiveWindow::<128>::new(128, 0.002);
+        for i in 0..60 {
+            window.push_value(100 + i % 5);
+        }
+        for i in 0..30 {
+            window.push_value(200 + i % 5);
+        }
+        assert!(window.shrinks() > 0);
+        assert_eq!(window.len(), 30);
+        assert!((window.mean() - 202.0).abs() < 1.0);
+    }
+}
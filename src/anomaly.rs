@@ -0,0 +1,174 @@
+use crate::byte_converter::LOG;
+use crate::feed::Push;
+use crate::logs::Logger;
+use crate::ring_buffer::RingBuffer;
+use crate::sums::ExactSums;
+
+// Value which scored beyond the threshold.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Anomaly {
+    pub value: i32,
+    // (value - mean) / std_dev of the window before the value was pushed, infinite when the window was constant
+    pub score: f32,
+    // index of the value in the stream, counted from 0
+    pub position: u64,
+}
+
+// Z-score detector. Every value is scored against mean and population standard deviation of the last window_size
+// values before it is pushed, values with |score| above threshold (in sigmas) are handed to on_anomaly.
+// Nothing is scored until the window is full. With exclude_outliers flagged values are not pushed into the window,
+// so single spikes don`t inflate the baseline, but a lasting level shift is then reported on every value.
+pub struct ZScoreDetector<F: FnMut(&Anomaly), const N: usize> {
+    window: RingBuffer<i32, N>,
+    threshold: f32,
+    exclude_outliers: bool,
+    sums: ExactSums,
+    position: u64,
+    anomalies: u64,
+    on_anomaly: F,
+}
+
+impl<F: FnMut(&Anomaly), const N: usize> ZScoreDetector<F, N> {
+    pub fn new(window_size: usize, threshold: f32, exclude_outliers: bool, on_anomaly: F) -> Self {
+        let threshold = if threshold.is_finite() && threshold > 0.0 {
+            threshold
+        } else {
+            LOG.warn("threshold has to be positive number of sigmas. Using 3");
+            3.0
+        };
+        Self {
+            window: RingBuffer::new(window_size),
+            threshold,
+            exclude_outliers,
+            sums: ExactSums::new(),
+            position: 0,
+            anomalies: 0,
+            on_anomaly,
+        }
+    }
+
+    pub fn threshold(&self) -> f32 {
+        self.threshold
+    }
+
+    // how many anomalies were reported so far
+    pub fn anomalies(&self) -> u64 {
+        self.anomalies
+    }
+
+    // values in the baseline window
+    pub fn len(&self) -> usize {
+        self.window.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.window.is_empty()
+    }
+
+    // score value against the window and push it, None while the window is not full
+    pub fn push_value(&mut self, value: i32) -> Option<f32> {
+        let score = self.score(value);
+        let position = self.position;
+        self.position += 1;
+
+        let flagged = matches!(score, Some(s) if s.abs() > self.threshold);
+        if let (true, Some(score)) = (flagged, score) {
+            self.anomalies += 1;
+            (self.on_anomaly)(&Anomaly {
+                value,
+                score,
+                position,
+            });
+        }
+        if !(flagged && self.exclude_outliers) {
+            if let Some(evicted) = self.window.push(value) {
+                self.sums.remove(evicted);
+            }
+            self.sums.add(value);
+        }
+        score
+    }
+
+    // z-score of value against the current window without pushing it
+    pub fn score(&self, value: i32) -> Option<f32> {
+        if !self.window.is_full() {
+            return None;
+        }
+        let (mean, variance) = self.sums.mean().zip(self.sums.population_variance())?;
+        let std_dev = variance.sqrt();
+        let diff = value as f64 - mean;
+        if std_dev == 0.0 {
+            // any change of constant signal is infinitely unlikely
+            return Some(if diff == 0.0 {
+                0.0
+            } else {
+                f32::INFINITY.copysign(diff as f32)
+            });
+        }
+        Some((diff / std_dev) as f32)
+    }
+}
+
+impl<F: FnMut(&Anomaly), const N: usize> Push for ZScoreDetector<F, N> {
+    fn push(&mut self, value: i32) {
+        self.push_value(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feed::ByteFeed;
+
+    #[test]
+    fn test_spike_is_reported() {
+        let mut reported = [None; 4];
+        let mut count = 0;
+        {
+            let mut feed =
+                ByteFeed::new(ZScoreDetector::<_, 8>::new(4, 2.0, false, |a: &Anomaly| {
+                    reported[count] = Some(*a);
+                    count += 1;
+                }));
+            // 9, 11, 9, 11 then 20
+            feed.write_no_std(&[
+                0, 0, 0, 9, 0, 0, 0, 11, 0, 0, 0, 9, 0, 0, 0, 11, 0, 0, 0, 20,
+            ]);
+            assert_eq!(feed.inner().anomalies(), 1);
+        }
+        assert_eq!(count, 1);
+        assert_eq!(
+            reported[0],
+            Some(Anomaly {
+                value: 20,
+                score: 10.0,
+                position: 4,
+            })
+        );
+    }
+
+    #[test]
+    fn test_exclude_outliers() {
+        let mut included = ZScoreDetector::<_, 4>::new(4, 3.0, false, |_: &Anomaly| {});
+        let mut excluded = ZScoreDetector::<_, 4>::new(4, 3.0, true, |_: &Anomaly| {});
+        for value in [10, 12, 10, 12, 100] {
+            included.push_value(value);
+            excluded.push_value(value);
+        }
+        // spike pulled mean above 13
+        assert!(included.score(13).unwrap() < 0.0);
+        // spike stayed out of the baseline
+        assert_eq!(excluded.score(13), Some(2.0));
+        assert_eq!(excluded.anomalies(), 1);
+    }
+
+    #[test]
+    fn test_constant_window() {
+        let mut detector = ZScoreDetector::<_, 2>::new(2, 0.0, false, |_: &Anomaly| {});
+        assert_eq!(detector.threshold(), 3.0);
+        assert_eq!(detector.push_value(5), None);
+        detector.push_value(5);
+        assert_eq!(detector.score(5), Some(0.0));
+        assert_eq!(detector.score(4), Some(f32::NEG_INFINITY));
+    }
+}
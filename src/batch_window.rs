@@ -0,0 +1,379 @@
+use crate::byte_converter::LOG;
+use crate::feed::Push;
+use crate::logs::Logger;
+use crate::sums::ExactSums;
+
+// Finalized statistics of one closed window. Positions are sample indexes or timestamps, whatever was pushed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WindowSummary {
+    // first position covered by the window
+    pub start: u64,
+    // first position after the window
+    pub end: u64,
+    pub count: u64,
+    pub mean: f32,
+    // population standard deviation, same as RollingStats::std_deviation
+    pub std_dev: f32,
+    pub min: i32,
+    pub max: i32,
+    // 0 for the first summary of the window, increased every time late values correct it
+    pub revision: u32,
+}
+
+// exact running sums of one window
+#[derive(Clone, Copy)]
+pub(crate) struct Accumulator {
+    sums: ExactSums,
+    min: i32,
+    max: i32,
+}
+
+impl Accumulator {
+    pub(crate) fn new() -> Self {
+        Self {
+            sums: ExactSums::new(),
+            min: i32::MAX,
+            max: i32::MIN,
+        }
+    }
+
+    pub(crate) fn add(&mut self, value: i32) {
+        self.sums.add(value);
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.sums.is_empty()
+    }
+
+    pub(crate) fn summary(&self, start: u64, end: u64) -> WindowSummary {
+        WindowSummary {
+            start,
+            end,
+            count: self.sums.count(),
+            mean: self.sums.mean().unwrap_or(0.0) as f32,
+            std_dev: self.sums.population_variance().unwrap_or(0.0).sqrt() as f32,
+            min: self.min,
+            max: self.max,
+            revision: 0,
+        }
+    }
+}
+
+// Non-overlapping windows of `size` positions, e.g. stats per 100 samples or per second.
+// Window [k * size, (k + 1) * size) is closed and handed to on_close as soon as a value from a later window
+// arrives, time moves past it with advance_to, or flush is called. Windows without values are not emitted.
+// Pushed through ByteFeed the position is the index of the sample.
+pub struct TumblingWindow<F: FnMut(&WindowSummary)> {
+    size: u64,
+    start: Option<u64>,
+    acc: Accumulator,
+    samples: u64,
+    on_close: F,
+}
+
+impl<F: FnMut(&WindowSummary)> TumblingWindow<F> {
+    pub fn new(size: u64, on_close: F) -> Self {
+        Self {
+            size: checked_size(size),
+            start: None,
+            acc: Accumulator::new(),
+            samples: 0,
+            on_close,
+        }
+    }
+
+    // positions are expected to be non decreasing
+    pub fn push_at(&mut self, position: u64, value: i32) {
+        let window_start = position - position % self.size;
+        match self.start {
+            Some(start) if window_start > start => {
+                self.close();
+                self.start = Some(window_start);
+            }
+            Some(start) if window_start < start => {
+                LOG.warn(
+                    "position is older than the open window, value is added to the open window. Use EventTimeWindow for out of order values",
+                );
+            }
+            Some(_) => {}
+            None => self.start = Some(window_start),
+        }
+        self.acc.add(value);
+    }
+
+    // close the open window when time passed its end without new values
+    pub fn advance_to(&mut self, now: u64) {
+        if matches!(self.start, Some(start) if start + self.size <= now) {
+            self.close();
+        }
+    }
+
+    // close the open window immediately, e.g. at the end of the stream
+    pub fn flush(&mut self) {
+        self.close();
+    }
+
+    fn close(&mut self) {
+        if let Some(start) = self.start.take() {
+            if !self.acc.is_empty() {
+                (self.on_close)(&self.acc.summary(start, start + self.size));
+            }
+        }
+        self.acc = Accumulator::new();
+    }
+}
+
+impl<F: FnMut(&WindowSummary)> Push for TumblingWindow<F> {
+    fn push(&mut self, value: i32) {
+        self.push_at(self.samples, value);
+        self.samples += 1;
+    }
+}
+
+// Overlapping windows of `size` positions starting every `slide` positions, so each value belongs to about
+// size / slide windows. Up to M windows are open at once, M has to be at least size / slide rounded up.
+// Window is closed and handed to on_close when a value at or after its end arrives, on advance_to or flush.
+pub struct HoppingWindow<F: FnMut(&WindowSummary), const M: usize> {
+    size: u64,
+    slide: u64,
+    // open windows (start, sums), unused slots are None
+    open: [Option<(u64, Accumulator)>; M],
+    samples: u64,
+    on_close: F,
+}
+
+impl<F: FnMut(&WindowSummary), const M: usize> HoppingWindow<F, M> {
+    pub fn new(size: u64, slide: u64, on_close: F) -> Self {
+        let size = checked_size(size);
+        let slide = if slide == 0 || slide > size {
+            LOG.warn("slide has to be in 1..=size range. Using slide = size");
+            size
+        } else {
+            slide
+        };
+        if size.div_ceil(slide) > M as u64 {
+            LOG.warn("M is smaller than size / slide, some windows won`t be tracked");
+        }
+        Self {
+            size,
+            slide,
+            open: [None; M],
+            samples: 0,
+            on_close,
+        }
+    }
+
+    // positions are expected to be non decreasing
+    pub fn push_at(&mut self, position: u64, value: i32) {
+        self.advance_to(position);
+
+        // every window with start in (position - size, position] which is a multiple of slide
+        let mut start = position - position % self.slide;
+        loop {
+            let slot = match self
+                .open
+                .iter()
+                .position(|w| matches!(w, Some((s, _)) if *s == start))
+            {
+                Some(slot) => Some(slot),
+                None => self.open.iter().position(|w| w.is_none()),
+            };
+            match slot.and_then(|slot| self.open.get_mut(slot)) {
+                Some(Some((_, acc))) => acc.add(value),
+                Some(empty) => {
+                    let mut acc = Accumulator::new();
+                    acc.add(value);
+                    *empty = Some((start, acc));
+                }
+                None => LOG.warn("no free slot for hopping window, value is not counted in it"),
+            }
+            if start < self.slide || start + self.size <= position + self.slide {
+                break;
+            }
+            start -= self.slide;
+        }
+    }
+
+    // close all windows which end at or before now, oldest first
+    pub fn advance_to(&mut self, now: u64) {
+        self.close_while(|start, size| start + size <= now);
+    }
+
+    // close all open windows, oldest first
+    pub fn flush(&mut self) {
+        self.close_while(|_, _| true);
+    }
+
+    fn close_while(&mut self, should_close: impl Fn(u64, u64) -> bool) {
+        loop {
+            let oldest = self
+                .open
+                .iter_mut()
+                .filter(|w| matches!(w, Some((start, _)) if should_close(*start, self.size)))
+                .min_by_key(|w| w.as_ref().map(|(start, _)| *start));
+            match oldest.and_then(|w| w.take()) {
+                Some((start, acc)) => (self.on_close)(&acc.summary(start, start + self.size)),
+                None => break,
+            }
+        }
+    }
+}
+
+impl<F: FnMut(&WindowSummary), const M: usize> Push for HoppingWindow<F, M> {
+    fn push(&mut self, value: i32) {
+        self.push_at(self.samples, value);
+        self.samples += 1;
+    }
+}
+
+// Window which stays open while values keep coming and closes after `gap` positions without any value.
+// Summary ends right after the last value of the session. Idle session is closed on the next push, on
+// advance_to or flush. Gap only makes sense with timestamps, so push values with push_at.
+pub struct SessionWindow<F: FnMut(&WindowSummary)> {
+    gap: u64,
+    // (first, last) position of the open session
+    session: Option<(u64, u64)>,
+    acc: Accumulator,
+    samples: u64,
+    on_close: F,
+}
+
+impl<F: FnMut(&WindowSummary)> SessionWindow<F> {
+    pub fn new(gap: u64, on_close: F) -> Self {
+        Self {
+            gap: checked_size(gap),
+            session: None,
+            acc: Accumulator::new(),
+            samples: 0,
+            on_close,
+        }
+    }
+
+    // positions are expected to be non decreasing
+    pub fn push_at(&mut self, position: u64, value: i32) {
+        self.advance_to(position);
+        self.session = match self.session {
+            Some((first, last)) => Some((first, last.max(position))),
+            None => Some((position, position)),
+        };
+        self.acc.add(value);
+    }
+
+    // close the session when nothing arrived for more than gap positions
+    pub fn advance_to(&mut self, now: u64) {
+        if matches!(self.session, Some((_, last)) if now.saturating_sub(last) > self.gap) {
+            self.close();
+        }
+    }
+
+    pub fn flush(&mut self) {
+        self.close();
+    }
+
+    fn close(&mut self) {
+        if let Some((first, last)) = self.session.take() {
+            (self.on_close)(&self.acc.summary(first, last + 1));
+        }
+        self.acc = Accumulator::new();
+    }
+}
+
+impl<F: FnMut(&WindowSummary)> Push for SessionWindow<F> {
+    fn push(&mut self, value: i32) {
+        self.push_at(self.samples, value);
+        self.samples += 1;
+    }
+}
+
+fn checked_size(size: u64) -> u64 {
+    if size > 0 {
+        size
+    } else {
+        LOG.warn("window size has to be positive. Using 1");
+        1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feed::ByteFeed;
+
+    #[test]
+    fn test_tumbling_by_samples() {
+        let mut closed = [None; 4];
+        let mut count = 0;
+        {
+            let mut feed = ByteFeed::new(TumblingWindow::new(2, |s: &WindowSummary| {
+                closed[count] = Some(*s);
+                count += 1;
+            }));
+            feed.write_no_std(&[0, 0, 0, 1, 0, 0, 0, 3, 0, 0, 0, 5, 0, 0, 0, 9, 0, 0, 0, 7]);
+            feed.inner_mut().flush();
+        }
+        assert_eq!(count, 3);
+        let first = closed[0].unwrap();
+        assert_eq!((first.start, first.end, first.count), (0, 2, 2));
+        assert_eq!(
+            (first.mean, first.std_dev, first.min, first.max),
+            (2.0, 1.0, 1, 3)
+        );
+        assert_eq!(closed[1].unwrap().mean, 7.0);
+        assert_eq!(closed[2].unwrap().count, 1);
+    }
+
+    #[test]
+    fn test_tumbling_by_time_skips_empty_windows() {
+        let mut starts = [0u64; 4];
+        let mut count = 0;
+        {
+            let mut window = TumblingWindow::new(1_000, |s: &WindowSummary| {
+                starts[count] = s.start;
+                count += 1;
+            });
+            window.push_at(10, 1);
+            window.push_at(999, 1);
+            window.push_at(3_500, 1);
+            window.advance_to(4_000);
+        }
+        assert_eq!(&starts[..count], &[0, 3_000]);
+    }
+
+    #[test]
+    fn test_hopping() {
+        let mut means = [0.0; 8];
+        let mut count = 0;
+        {
+            let mut window = HoppingWindow::<_, 2>::new(4, 2, |s: &WindowSummary| {
+                means[count] = s.mean;
+                count += 1;
+            });
+            for (position, value) in [(0, 1), (1, 1), (2, 3), (3, 3), (4, 5), (5, 5)] {
+                window.push_at(position, value);
+            }
+            window.flush();
+        }
+        // windows [0, 4), [2, 6), [4, 8)
+        assert_eq!(&means[..count], &[2.0, 4.0, 5.0]);
+    }
+
+    #[test]
+    fn test_session_closes_after_gap() {
+        let mut sessions = [(0, 0, 0); 4];
+        let mut count = 0;
+        {
+            let mut window = SessionWindow::new(10, |s: &WindowSummary| {
+                sessions[count] = (s.start, s.end, s.count);
+                count += 1;
+            });
+            window.push_at(0, 1);
+            window.push_at(5, 1);
+            window.push_at(15, 1);
+            window.push_at(40, 1);
+            window.advance_to(51);
+        }
+        assert_eq!(&sessions[..count], &[(0, 16, 3), (40, 41, 1)]);
+    }
+}
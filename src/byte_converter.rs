@@ -80,7 +80,7 @@ impl<T: Default + Copy, const N: usize> FixedArray<T, N> {
     }
 
     // array iterater
-    pub fn iter(&self) -> Iter<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
         self.data[..self.counter].iter()
     }
 
@@ -165,6 +165,11 @@ pub struct ByteConverter {
     litle_endian: bool,
     // sum of all input i32 values. It is convinient to count it while converting input
     sum: i32, // remove pub TODO
+    // how many values at the start of buf_current were decoded from the current write call,
+    // rest of buf_current is filled from buf_last
+    fresh_len: usize,
+    // offset in the current write call where values which didn`t fit into the window start
+    skipped_from: usize,
 }
 
 impl ByteConverter {
@@ -177,6 +182,8 @@ impl ByteConverter {
                 buf_remainder: FixedArray::<u8, 4>::new(),
                 litle_endian: false,
                 sum: 0,
+                fresh_len: 0,
+                skipped_from: 0,
             }
         } else {
             LOG.warn("please use real windo_size for input. Using default value = 3");
@@ -187,6 +194,8 @@ impl ByteConverter {
                 buf_remainder: FixedArray::<u8, 4>::new(),
                 litle_endian: false,
                 sum: 0,
+                fresh_len: 0,
+                skipped_from: 0,
             }
         }
     }
@@ -196,6 +205,11 @@ impl ByteConverter {
         self.window_size * 4
     }
 
+    // window size in values
+    pub fn get_window_len(&self) -> usize {
+        self.window_size
+    }
+
     pub fn get_sum(&self) -> &i32 {
         &self.sum
     }
@@ -205,6 +219,27 @@ impl ByteConverter {
         &self.buf_current
     }
 
+    // values decoded only from the last write call, without values borrowed from the previous one
+    pub fn get_fresh(&self) -> &[i32] {
+        self.buf_current.get(0..self.fresh_len).unwrap_or(&[])
+    }
+
+    // values of the current write call (buf has to be the same) which didn`t fit into the window. They are
+    // decoded in the same byte order as the window values, incomplete tail is dropped same as by the window.
+    pub fn get_skipped<'a>(&self, buf: &'a [u8]) -> impl Iterator<Item = i32> + 'a {
+        let litle_endian = self.litle_endian;
+        buf.get(self.skipped_from..)
+            .unwrap_or(&[])
+            .chunks_exact(4)
+            .map(move |chunk| {
+                if litle_endian {
+                    LittleEndian::read_i32(chunk)
+                } else {
+                    BigEndian::read_i32(chunk)
+                }
+            })
+    }
+
     pub fn set_sum(&mut self, value: i32) {
         self.sum = value;
     }
@@ -214,6 +249,33 @@ impl ByteConverter {
         self.buf_current.clear();
     }
 
+    // forget all values, sums and incomplete bytes, as if nothing was written yet
+    pub fn reset(&mut self) {
+        self.buf_last.clear();
+        self.buf_current.clear();
+        self.buf_remainder.clear();
+        self.sum = 0;
+        self.fresh_len = 0;
+    }
+
+    // keep only values from the last write call starting at index from, everything older is forgotten.
+    // Kept values are also saved into buf_last to fill window_size gaps in the next write calls.
+    pub fn retain_fresh(&mut self, from: usize) {
+        let mut kept = FixedArray::<i32, 255>::new();
+        if let Some(values) = self.get_fresh().get(from..) {
+            if !values.is_empty() {
+                _ = kept.extend_by_slice(values);
+            }
+        }
+        self.reset();
+        if !kept.is_empty() {
+            _ = self.buf_current.extend_by_array(&kept);
+            _ = self.buf_last.extend_by_array(&kept);
+        }
+        self.sum = kept.iter().sum();
+        self.fresh_len = kept.len();
+    }
+
     // if not add next iteration into buf_current and break
     // or add into buf_remainder
     pub fn read_little_endians(&mut self, buf: &[u8], start_index: usize) {
@@ -227,6 +289,7 @@ impl ByteConverter {
             self.buf_current.push(value);
             self.sum += value;
         }
+        self.skipped_from = max_size - slice.remainder().len();
 
         // reminder bigger than window_size is not interested
         if !slice.remainder().is_empty() && buf.len() <= window_size {
@@ -235,7 +298,7 @@ impl ByteConverter {
     }
 
     pub fn read_big_endians(&mut self, buf: &[u8], start_index: usize) {
-        self.litle_endian = true;
+        self.litle_endian = false;
         let window_size = self.get_window_size();
         let max_size = cmp::min(buf.len(), window_size);
         let mut slice = buf[start_index..max_size].chunks_exact(4);
@@ -245,6 +308,7 @@ impl ByteConverter {
             self.buf_current.push(value);
             self.sum += value;
         }
+        self.skipped_from = max_size - slice.remainder().len();
 
         // reminder bigger than window_size is not interested
         if !slice.remainder().is_empty() && buf.len() <= window_size {
@@ -319,6 +383,8 @@ impl ByteConverter {
     // in this fn we are taking current write and save it into buf_currenct
     // For now skipping returning number of succesfully converted bytes.
     pub fn convert_bytes_to_i32(&mut self, buf: &[u8]) {
+        self.fresh_len = 0;
+        self.skipped_from = buf.len();
         // split bytes sequence by 4
 
         // basically we need at least 8 bytes for mean(), but for now we don`t support less numbers in
@@ -359,6 +425,8 @@ impl ByteConverter {
             self.buf_remainder.clear();
         }
 
+        self.fresh_len = self.buf_current.len();
+
         // if we are getting less values than windows size. We need to take a look into the self.buf_last
         // for some values to get full window size.
         if !self.buf_last.is_empty() && self.buf_current.len() < self.window_size {
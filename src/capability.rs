@@ -0,0 +1,87 @@
+// d2 constant for moving ranges of 2 consecutive values, mean moving range / D2 estimates within sigma
+const D2: f64 = 1.128;
+
+// Process capability against lower and upper specification limits.
+// Cp and Cpk use within sigma estimated from the mean moving range of consecutive values, so slow drifts don`t
+// inflate it. Pp and Ppk use overall sample standard deviation of the window.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Capability {
+    pub mean: f32,
+    pub within_sigma: f32,
+    pub overall_sigma: f32,
+    // (usl - lsl) / (6 * within_sigma)
+    pub cp: f32,
+    // distance of the mean to the nearer limit / (3 * within_sigma)
+    pub cpk: f32,
+    pub pp: f32,
+    pub ppk: f32,
+    // share of values in the window below lsl or above usl
+    pub fraction_out_of_spec: f32,
+}
+
+impl Capability {
+    // values in the order they arrived. None for less than 2 values, lsl >= usl or zero sigma.
+    pub fn from_values(values: &[i32], lsl: f32, usl: f32) -> Option<Capability> {
+        let n = values.len();
+        if n < 2 || lsl.partial_cmp(&usl) != Some(core::cmp::Ordering::Less) {
+            return None;
+        }
+        let (lsl, usl) = (lsl as f64, usl as f64);
+        let sum: i64 = values.iter().map(|v| *v as i64).sum();
+        let mean = sum as f64 / n as f64;
+        let squares: f64 = values
+            .iter()
+            .map(|v| (*v as f64 - mean) * (*v as f64 - mean))
+            .sum();
+        let overall_sigma = (squares / (n - 1) as f64).sqrt();
+        let moving_range: u64 = values
+            .windows(2)
+            .map(|pair| (pair[1] as i64 - pair[0] as i64).unsigned_abs())
+            .sum();
+        let within_sigma = moving_range as f64 / (n - 1) as f64 / D2;
+        if overall_sigma == 0.0 || within_sigma == 0.0 {
+            return None;
+        }
+        let out_of_spec = values
+            .iter()
+            .filter(|v| (**v as f64) < lsl || (**v as f64) > usl)
+            .count();
+
+        let nearer_limit = (usl - mean).min(mean - lsl);
+        Some(Capability {
+            mean: mean as f32,
+            within_sigma: within_sigma as f32,
+            overall_sigma: overall_sigma as f32,
+            cp: ((usl - lsl) / (6.0 * within_sigma)) as f32,
+            cpk: (nearer_limit / (3.0 * within_sigma)) as f32,
+            pp: ((usl - lsl) / (6.0 * overall_sigma)) as f32,
+            ppk: (nearer_limit / (3.0 * overall_sigma)) as f32,
+            fraction_out_of_spec: (out_of_spec as f64 / n as f64) as f32,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_indices() {
+        let capability = Capability::from_values(&[1, 3, 2, 4, 3], 0.0, 6.0).unwrap();
+        assert_eq!(capability.mean, 2.6);
+        assert!((capability.within_sigma - 1.329_787).abs() < 1e-5);
+        assert!((capability.cp - 0.752).abs() < 1e-5);
+        assert!((capability.cpk - 0.651_733).abs() < 1e-5);
+        assert!((capability.pp - 0.877_058).abs() < 1e-5);
+        assert!((capability.ppk - 0.760_117).abs() < 1e-5);
+        assert_eq!(capability.fraction_out_of_spec, 0.0);
+    }
+
+    #[test]
+    fn test_out_of_spec() {
+        let capability = Capability::from_values(&[1, 5, 9, 5], 2.0, 8.0).unwrap();
+        assert_eq!(capability.fraction_out_of_spec, 0.5);
+        assert_eq!(Capability::from_values(&[1, 1, 1], 0.0, 2.0), None);
+        assert_eq!(Capability::from_values(&[1, 2], 2.0, 2.0), None);
+    }
+}
@@ -0,0 +1,332 @@
+use crate::byte_converter::LOG;
+use crate::feed::Push;
+use crate::logs::Logger;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+}
+
+// Confirmed change of the stream level. Positions are indexes of values seen by the detector, counted from 0.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChangePoint {
+    // value which confirmed the change
+    pub detected_at: u64,
+    // estimated first value after the change, detection always lags behind it
+    pub start: u64,
+    pub direction: Direction,
+}
+
+// Sequential detector of persistent shifts, fed one value at a time. After a confirmed change the detector starts
+// again from scratch, so every shift is reported once.
+pub trait ChangeDetector {
+    fn update(&mut self, value: i32) -> Option<ChangePoint>;
+    // forget everything seen so far, position counter keeps running
+    fn reset(&mut self);
+}
+
+// Two sided tabular CUSUM around a known target mean. Deviations larger than k (allowance, usually half of the
+// shift to detect) are accumulated separately up and down, change is confirmed when a sum exceeds h.
+// k and h are in units of the values, commonly k = 0.5 sigma and h = 4 or 5 sigma. On a confirmed change target
+// moves to the estimated new level target +- (k + sum / run length), so a persistent shift is reported once.
+pub struct Cusum {
+    target: f64,
+    k: f64,
+    h: f64,
+    upper: f64,
+    lower: f64,
+    // first value of the current positive run of each sum
+    upper_start: u64,
+    lower_start: u64,
+    position: u64,
+    last_change: Option<ChangePoint>,
+}
+
+impl Cusum {
+    pub fn new(target: f32, k: f32, h: f32) -> Self {
+        let k = if k.is_finite() && k >= 0.0 {
+            k
+        } else {
+            LOG.warn("allowance k can`t be negative. Using 0");
+            0.0
+        };
+        let h = if h.is_finite() && h > 0.0 {
+            h
+        } else {
+            LOG.warn("decision interval h has to be positive. Using 5");
+            5.0
+        };
+        Self {
+            target: target as f64,
+            k: k as f64,
+            h: h as f64,
+            upper: 0.0,
+            lower: 0.0,
+            upper_start: 0,
+            lower_start: 0,
+            position: 0,
+            last_change: None,
+        }
+    }
+
+    // current target, moved to the new level by every confirmed change
+    pub fn target(&self) -> f32 {
+        self.target as f32
+    }
+
+    // e.g. known mean of the new level after a change was confirmed
+    pub fn set_target(&mut self, target: f32) {
+        self.target = target as f64;
+        self.reset();
+    }
+
+    // accumulated deviation above target + k
+    pub fn upper(&self) -> f32 {
+        self.upper as f32
+    }
+
+    // accumulated deviation below target - k
+    pub fn lower(&self) -> f32 {
+        self.lower as f32
+    }
+
+    pub fn last_change(&self) -> Option<ChangePoint> {
+        self.last_change
+    }
+}
+
+impl ChangeDetector for Cusum {
+    fn update(&mut self, value: i32) -> Option<ChangePoint> {
+        let position = self.position;
+        self.position += 1;
+        let x = value as f64;
+
+        if self.upper == 0.0 {
+            self.upper_start = position;
+        }
+        if self.lower == 0.0 {
+            self.lower_start = position;
+        }
+        self.upper = (self.upper + x - self.target - self.k).max(0.0);
+        self.lower = (self.lower + self.target - self.k - x).max(0.0);
+
+        let change = if self.upper > self.h {
+            let run = (position - self.upper_start + 1) as f64;
+            self.target += self.k + self.upper / run;
+            Some((self.upper_start, Direction::Up))
+        } else if self.lower > self.h {
+            let run = (position - self.lower_start + 1) as f64;
+            self.target -= self.k + self.lower / run;
+            Some((self.lower_start, Direction::Down))
+        } else {
+            None
+        };
+        let change = change.map(|(start, direction)| ChangePoint {
+            detected_at: position,
+            start,
+            direction,
+        });
+        if change.is_some() {
+            self.last_change = change;
+            self.reset();
+        }
+        change
+    }
+
+    fn reset(&mut self) {
+        self.upper = 0.0;
+        self.lower = 0.0;
+    }
+}
+
+impl Push for Cusum {
+    fn push(&mut self, value: i32) {
+        self.update(value);
+    }
+}
+
+// Page-Hinkley test, which needs no target: deviations from the running mean of values since the last change are
+// accumulated minus delta (magnitude of changes to ignore), change is confirmed when the sum rises more than lambda
+// above its minimum (or falls more than lambda below its maximum for a drop).
+pub struct PageHinkley {
+    delta: f64,
+    lambda: f64,
+    count: u64,
+    mean: f64,
+    up_sum: f64,
+    up_min: f64,
+    down_sum: f64,
+    down_max: f64,
+    // value after the extreme of each sum, where the change most likely started
+    up_start: u64,
+    down_start: u64,
+    position: u64,
+    last_change: Option<ChangePoint>,
+}
+
+impl PageHinkley {
+    pub fn new(delta: f32, lambda: f32) -> Self {
+        let delta = if delta.is_finite() && delta >= 0.0 {
+            delta
+        } else {
+            LOG.warn("delta can`t be negative. Using 0");
+            0.0
+        };
+        let lambda = if lambda.is_finite() && lambda > 0.0 {
+            lambda
+        } else {
+            LOG.warn("threshold lambda has to be positive. Using 50");
+            50.0
+        };
+        Self {
+            delta: delta as f64,
+            lambda: lambda as f64,
+            count: 0,
+            mean: 0.0,
+            up_sum: 0.0,
+            up_min: 0.0,
+            down_sum: 0.0,
+            down_max: 0.0,
+            up_start: 0,
+            down_start: 0,
+            position: 0,
+            last_change: None,
+        }
+    }
+
+    // running mean of values since the last change
+    pub fn mean(&self) -> f32 {
+        self.mean as f32
+    }
+
+    pub fn last_change(&self) -> Option<ChangePoint> {
+        self.last_change
+    }
+}
+
+impl ChangeDetector for PageHinkley {
+    fn update(&mut self, value: i32) -> Option<ChangePoint> {
+        let position = self.position;
+        self.position += 1;
+        if self.count == 0 {
+            self.up_start = position;
+            self.down_start = position;
+        }
+        let x = value as f64;
+        self.count += 1;
+        self.mean += (x - self.mean) / self.count as f64;
+
+        self.up_sum += x - self.mean - self.delta;
+        if self.up_sum < self.up_min {
+            self.up_min = self.up_sum;
+            self.up_start = position + 1;
+        }
+        self.down_sum += x - self.mean + self.delta;
+        if self.down_sum > self.down_max {
+            self.down_max = self.down_sum;
+            self.down_start = position + 1;
+        }
+
+        let change = if self.up_sum - self.up_min > self.lambda {
+            Some((self.up_start, Direction::Up))
+        } else if self.down_max - self.down_sum > self.lambda {
+            Some((self.down_start, Direction::Down))
+        } else {
+            None
+        };
+        let change = change.map(|(start, direction)| ChangePoint {
+            detected_at: position,
+            start: start.min(position),
+            direction,
+        });
+        if change.is_some() {
+            self.last_change = change;
+            self.reset();
+        }
+        change
+    }
+
+    fn reset(&mut self) {
+        self.count = 0;
+        self.mean = 0.0;
+        self.up_sum = 0.0;
+        self.up_min = 0.0;
+        self.down_sum = 0.0;
+        self.down_max = 0.0;
+    }
+}
+
+impl Push for PageHinkley {
+    fn push(&mut self, value: i32) {
+        self.update(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cusum_shift_up_and_down() {
+        let mut cusum = Cusum::new(10.0, 0.5, 4.0);
+        let mut changes = [None; 2];
+        let mut count = 0;
+        for value in [10, 9, 11, 10, 12, 12, 12, 10, 8, 8, 8] {
+            if let Some(change) = cusum.update(value) {
+                changes[count] = Some(change);
+                count += 1;
+            }
+        }
+        assert_eq!(count, 2);
+        assert_eq!(
+            changes[0],
+            Some(ChangePoint {
+                detected_at: 6,
+                start: 4,
+                direction: Direction::Up,
+            })
+        );
+        // target moved to 12, so the drop starts already with 10
+        assert_eq!(
+            changes[1].map(|c| (c.start, c.direction)),
+            Some((7, Direction::Down))
+        );
+        assert_eq!(cusum.last_change(), changes[1]);
+    }
+
+    #[test]
+    fn test_cusum_reports_persistent_shift_once() {
+        let mut cusum = Cusum::new(10.0, 0.5, 4.0);
+        let mut count = 0;
+        // shift 2 is confirmed after about h / (2 - k) = 3 values, new level is held much longer
+        for value in [10, 10, 10, 10].into_iter().chain([12; 20]) {
+            if cusum.update(value).is_some() {
+                count += 1;
+            }
+        }
+        assert_eq!(count, 1);
+        assert_eq!(cusum.target(), 12.0);
+    }
+
+    #[test]
+    fn test_page_hinkley() {
+        let mut detector = PageHinkley::new(0.5, 5.0);
+        for value in [0, 0, 0, 0, 0] {
+            assert_eq!(detector.update(value), None);
+        }
+        assert_eq!(
+            detector.update(10),
+            Some(ChangePoint {
+                detected_at: 5,
+                start: 5,
+                direction: Direction::Up,
+            })
+        );
+        // detector starts again from the new level
+        for value in [10, 10, 10] {
+            assert_eq!(detector.update(value), None);
+        }
+        assert_eq!(detector.mean(), 10.0);
+    }
+}
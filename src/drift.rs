@@ -0,0 +1,285 @@
+use crate::byte_converter::LOG;
+use crate::feed::Push;
+use crate::histogram::RollingHistogram;
+use crate::logs::Logger;
+use crate::moments::RollingMoments;
+
+// share used instead of empty bins, otherwise a single empty bin makes PSI and KL divergence infinite
+const EMPTY_BIN_SHARE: f64 = 1e-4;
+
+// Summary of a known good period, histogram with the same bins as the live window plus moments.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Baseline<const B: usize> {
+    counts: [u32; B],
+    underflow: u32,
+    overflow: u32,
+    len: usize,
+    mean: f32,
+    std_dev: f32,
+}
+
+impl<const B: usize> Baseline<B> {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn mean(&self) -> f32 {
+        self.mean
+    }
+
+    pub fn std_deviation(&self) -> f32 {
+        self.std_dev
+    }
+
+    // counts of regular bins
+    pub fn counts(&self) -> &[u32; B] {
+        &self.counts
+    }
+}
+
+// Comparison of the live window with the baseline. Bins include underflow and overflow.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DriftReport {
+    // Population Stability Index, sum of (live - base) * ln(live / base) over bin shares
+    pub psi: f32,
+    // KL divergence of the live distribution from the baseline in nats
+    pub kl_divergence: f32,
+    // two sample Kolmogorov-Smirnov statistic evaluated at bin edges
+    pub ks_statistic: f32,
+    // live mean minus baseline mean
+    pub mean_shift: f32,
+    // live std deviation / baseline std deviation, None for constant baseline
+    pub std_ratio: Option<f32>,
+}
+
+// Drift is raised when any statistic exceeds its threshold.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DriftThresholds {
+    pub psi: f32,
+    pub kl_divergence: f32,
+    pub ks_statistic: f32,
+}
+
+// PSI above 0.2 is commonly read as significant shift
+impl Default for DriftThresholds {
+    fn default() -> Self {
+        Self {
+            psi: 0.2,
+            kl_divergence: 0.1,
+            ks_statistic: 0.1,
+        }
+    }
+}
+
+impl DriftThresholds {
+    pub fn exceeded_by(&self, report: &DriftReport) -> bool {
+        report.psi > self.psi
+            || report.kl_divergence > self.kl_divergence
+            || report.ks_statistic > self.ks_statistic
+    }
+}
+
+// Live window compared with a frozen baseline on every push. on_drift gets the report when the window starts to
+// drift, further pushes are not reported until the window gets back under all thresholds. Nothing is compared
+// until the window is full.
+pub struct DriftMonitor<F: FnMut(&DriftReport), const B: usize, const N: usize> {
+    histogram: RollingHistogram<B, N>,
+    moments: RollingMoments<N>,
+    baseline: Option<Baseline<B>>,
+    thresholds: DriftThresholds,
+    drifting: bool,
+    events: u64,
+    on_drift: F,
+}
+
+impl<F: FnMut(&DriftReport), const B: usize, const N: usize> DriftMonitor<F, B, N> {
+    // B bins between min and max, same as RollingHistogram
+    pub fn new(
+        min: i32,
+        max: i32,
+        window_size: usize,
+        thresholds: DriftThresholds,
+        on_drift: F,
+    ) -> Self {
+        Self {
+            histogram: RollingHistogram::new(min, max, window_size),
+            moments: RollingMoments::new(window_size),
+            baseline: None,
+            thresholds,
+            drifting: false,
+            events: 0,
+            on_drift,
+        }
+    }
+
+    // store the current window as baseline, returns false for empty window
+    pub fn freeze_baseline(&mut self) -> bool {
+        if self.histogram.is_empty() {
+            LOG.warn("baseline can`t be frozen from empty window");
+            return false;
+        }
+        self.baseline = Some(Baseline {
+            counts: *self.histogram.counts(),
+            underflow: self.histogram.underflow(),
+            overflow: self.histogram.overflow(),
+            len: self.histogram.len(),
+            mean: self.moments.mean(),
+            std_dev: self.moments.std_deviation(),
+        });
+        self.drifting = false;
+        true
+    }
+
+    // baseline saved from another monitor with the same bins, e.g. after restart
+    pub fn set_baseline(&mut self, baseline: Baseline<B>) {
+        self.baseline = Some(baseline);
+        self.drifting = false;
+    }
+
+    pub fn baseline(&self) -> Option<&Baseline<B>> {
+        self.baseline.as_ref()
+    }
+
+    pub fn is_drifting(&self) -> bool {
+        self.drifting
+    }
+
+    // how many times drift was raised
+    pub fn events(&self) -> u64 {
+        self.events
+    }
+
+    pub fn len(&self) -> usize {
+        self.histogram.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.histogram.is_empty()
+    }
+
+    pub fn push_value(&mut self, value: i32) {
+        self.histogram.push_value(value);
+        self.moments.push_value(value);
+        if self.histogram.len() < self.moments.window().window_size() {
+            return;
+        }
+        if let Some(report) = self.report() {
+            let drifting = self.thresholds.exceeded_by(&report);
+            if drifting && !self.drifting {
+                self.events += 1;
+                (self.on_drift)(&report);
+            }
+            self.drifting = drifting;
+        }
+    }
+
+    // compare the live window with the baseline, None without baseline or with empty window
+    pub fn report(&self) -> Option<DriftReport> {
+        let baseline = self.baseline.as_ref()?;
+        if self.histogram.is_empty() || baseline.is_empty() {
+            return None;
+        }
+        let live_len = self.histogram.len() as f64;
+        let base_len = baseline.len as f64;
+        let live_bins = bins(
+            self.histogram.underflow(),
+            self.histogram.counts(),
+            self.histogram.overflow(),
+        );
+        let base_bins = bins(baseline.underflow, &baseline.counts, baseline.overflow);
+
+        let (mut psi, mut kl, mut ks) = (0.0, 0.0, 0.0f64);
+        let (mut live_cdf, mut base_cdf) = (0.0, 0.0);
+        for (live, base) in live_bins.zip(base_bins) {
+            let live = live as f64 / live_len;
+            let base = base as f64 / base_len;
+            live_cdf += live;
+            base_cdf += base;
+            ks = ks.max((live_cdf - base_cdf).abs());
+            let (live, base) = (live.max(EMPTY_BIN_SHARE), base.max(EMPTY_BIN_SHARE));
+            psi += (live - base) * (live / base).ln();
+            kl += live * (live / base).ln();
+        }
+
+        let std_dev = self.moments.std_deviation();
+        Some(DriftReport {
+            psi: psi as f32,
+            kl_divergence: kl as f32,
+            ks_statistic: ks as f32,
+            mean_shift: self.moments.mean() - baseline.mean,
+            std_ratio: (baseline.std_dev > 0.0).then(|| std_dev / baseline.std_dev),
+        })
+    }
+}
+
+// underflow, regular bins and overflow in order of values
+fn bins<'a>(underflow: u32, counts: &'a [u32], overflow: u32) -> impl Iterator<Item = u32> + 'a {
+    core::iter::once(underflow)
+        .chain(counts.iter().copied())
+        .chain(core::iter::once(overflow))
+}
+
+impl<F: FnMut(&DriftReport), const B: usize, const N: usize> Push for DriftMonitor<F, B, N> {
+    fn push(&mut self, value: i32) {
+        self.push_value(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shifted_window_raises_drift() {
+        let mut events = 0;
+        {
+            let mut monitor = DriftMonitor::<_, 10, 10>::new(
+                0,
+                10,
+                10,
+                DriftThresholds::default(),
+                |_: &DriftReport| events += 1,
+            );
+            assert!(!monitor.freeze_baseline());
+            for value in 0..10 {
+                monitor.push_value(value);
+            }
+            assert!(monitor.freeze_baseline());
+            let report = monitor.report().unwrap();
+            assert_eq!((report.psi, report.ks_statistic), (0.0, 0.0));
+
+            for value in [5, 5, 6, 6, 7, 7, 8, 8, 9, 9] {
+                monitor.push_value(value);
+            }
+            let report = monitor.report().unwrap();
+            assert_eq!(report.ks_statistic, 0.5);
+            assert!(report.psi > 3.0);
+            assert!((report.kl_divergence - 0.69).abs() < 0.01);
+            assert_eq!(report.mean_shift, 2.5);
+            assert!(monitor.is_drifting());
+        }
+        // reported once when the drift started
+        assert_eq!(events, 1);
+    }
+
+    #[test]
+    fn test_thresholds() {
+        let thresholds = DriftThresholds::default();
+        let report = DriftReport {
+            psi: 0.1,
+            kl_divergence: 0.05,
+            ks_statistic: 0.2,
+            mean_shift: 0.0,
+            std_ratio: None,
+        };
+        assert!(thresholds.exceeded_by(&report));
+        assert!(!thresholds.exceeded_by(&DriftReport {
+            ks_statistic: 0.05,
+            ..report
+        }));
+    }
+}
@@ -0,0 +1,131 @@
+use rand::thread_rng;
+use rand_distr::{Distribution, Normal};
+
+use crate::byte_converter::LOG;
+use crate::feed::Push;
+use crate::logs::Logger;
+
+// Exponentially weighted mean and variance. Recent values dominate instead of a hard cut window, so no values
+// are stored at all, only the current estimates. Feed it from bytes with ByteFeed<Ewma>.
+pub struct Ewma {
+    // weight of the newest value, 0 < alpha <= 1
+    alpha: f64,
+    mean: f64,
+    variance: f64,
+    count: u64,
+}
+
+impl Ewma {
+    pub fn with_alpha(alpha: f32) -> Self {
+        let alpha = if alpha > 0.0 && alpha <= 1.0 {
+            alpha as f64
+        } else {
+            LOG.warn("alpha has to be in (0, 1] range. Using default value = 0.1");
+            0.1
+        };
+        Self {
+            alpha,
+            mean: 0.0,
+            variance: 0.0,
+            count: 0,
+        }
+    }
+
+    // weight of a value drops to one half after half_life newer values
+    pub fn with_half_life(half_life: f32) -> Self {
+        if half_life > 0.0 {
+            Self::with_alpha((1.0 - 0.5f64.powf(1.0 / half_life as f64)) as f32)
+        } else {
+            LOG.warn("half_life has to be positive. Using alpha = 0.1");
+            Self::with_alpha(0.1)
+        }
+    }
+
+    pub fn alpha(&self) -> f32 {
+        self.alpha as f32
+    }
+
+    pub fn push_value(&mut self, value: i32) {
+        let value = value as f64;
+        if self.count == 0 {
+            // first value is the whole history
+            self.mean = value;
+            self.variance = 0.0;
+        } else {
+            let diff = value - self.mean;
+            let increment = self.alpha * diff;
+            self.mean += increment;
+            self.variance = (1.0 - self.alpha) * (self.variance + diff * increment);
+        }
+        self.count += 1;
+    }
+
+    // number of values seen since start
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn mean(&self) -> f32 {
+        self.mean as f32
+    }
+
+    pub fn variance(&self) -> f32 {
+        self.variance as f32
+    }
+
+    pub fn std_deviation(&self) -> f32 {
+        self.variance.sqrt() as f32
+    }
+
+    // sample from normal distribution with exponentially weighted mean and standard deviation
+    pub fn std_distribution(&self) -> f32 {
+        if self.count == 0 {
+            LOG.error("std_distribution can`t be computed before first value");
+            return 0.0;
+        }
+        match Normal::new(self.mean(), self.std_deviation()) {
+            Ok(normal_dis) => normal_dis.sample(&mut thread_rng()),
+            Err(_) => {
+                LOG.error("std_deviation is not finite, can`t sample");
+                0.0
+            }
+        }
+    }
+}
+
+impl Push for Ewma {
+    fn push(&mut self, value: i32) {
+        self.push_value(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feed::ByteFeed;
+
+    #[test]
+    fn test_mean_and_variance() {
+        let mut ewma = Ewma::with_alpha(0.5);
+        ewma.push_value(2);
+        assert_eq!(ewma.mean(), 2.0);
+        ewma.push_value(4);
+        // mean 3, variance 0.5 * (0 + 2 * 1)
+        assert_eq!(ewma.mean(), 3.0);
+        assert_eq!(ewma.variance(), 1.0);
+        ewma.push_value(3);
+        assert_eq!(ewma.mean(), 3.0);
+        assert_eq!(ewma.variance(), 0.5);
+        assert_ne!(ewma.std_distribution(), 0.0);
+    }
+
+    #[test]
+    fn test_half_life_and_byte_feed() {
+        let ewma = Ewma::with_half_life(1.0);
+        assert_eq!(ewma.alpha(), 0.5);
+        let mut feed = ByteFeed::new(ewma);
+        feed.write_no_std(&[0, 0, 0, 2, 0, 0, 0, 4]);
+        assert_eq!(feed.inner().count(), 2);
+        assert_eq!(feed.inner().mean(), 3.0);
+    }
+}
@@ -0,0 +1,202 @@
+use byteorder::{BigEndian, ByteOrder as _, LittleEndian};
+
+// Anything that consumes decoded i32 values one by one. Rolling statistics implement it so the same
+// byte decoding pipeline can feed all of them.
+pub trait Push {
+    fn push(&mut self, value: i32);
+}
+
+// Rolling statistic which can drop its oldest value before its count window is full. Then another policy than
+// count decides when values leave the window, e.g. TimeEvicted drops values older than a duration.
+pub trait Evict: Push {
+    // drop the oldest value, false when the window is already empty
+    fn evict_oldest(&mut self) -> bool;
+    // number of values in the window
+    fn window_len(&self) -> usize;
+}
+
+// Order of bytes in one i32 value of the stream.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ByteOrder {
+    Big,
+    Little,
+}
+
+// Decodes a byte stream into i32 values and forwards them to any Push statistic in the order they arrived.
+// Byte order is fixed for the whole stream, it is not guessed from the bytes, so negative values and values
+// with a zero most significant byte are decoded right. Incomplete numbers are kept and completed in the
+// next write call.
+pub struct ByteFeed<S> {
+    byte_order: ByteOrder,
+    // bytes of a value which was not completed yet
+    pending: [u8; 4],
+    pending_len: usize,
+    inner: S,
+}
+
+impl<S: Push> ByteFeed<S> {
+    // big endian stream, same as the RollingStats default
+    pub fn new(inner: S) -> Self {
+        Self::with_byte_order(inner, ByteOrder::Big)
+    }
+
+    pub fn with_byte_order(inner: S, byte_order: ByteOrder) -> Self {
+        Self {
+            byte_order,
+            pending: [0; 4],
+            pending_len: 0,
+            inner,
+        }
+    }
+
+    pub fn byte_order(&self) -> ByteOrder {
+        self.byte_order
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    // returns number of values forwarded to the inner statistic. Every byte is consumed, even a single value or
+    // a part of it, because the inner statistic doesn`t need a full window from one write.
+    pub fn write_no_std(&mut self, buf: &[u8]) -> usize {
+        let mut forwarded = 0;
+        for byte in buf {
+            self.pending[self.pending_len] = *byte;
+            self.pending_len += 1;
+            if self.pending_len == 4 {
+                self.pending_len = 0;
+                let value = match self.byte_order {
+                    ByteOrder::Big => BigEndian::read_i32(&self.pending),
+                    ByteOrder::Little => LittleEndian::read_i32(&self.pending),
+                };
+                self.inner.push(value);
+                forwarded += 1;
+            }
+        }
+        forwarded
+    }
+}
+
+#[cfg(feature = "std")]
+impl<S: Push> std::io::Write for ByteFeed<S> {
+    // whole buf is consumed, values are decoded and incomplete number is saved for the next call
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.write_no_std(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Count {
+        values: usize,
+        last: i32,
+    }
+
+    impl Push for Count {
+        fn push(&mut self, value: i32) {
+            self.values += 1;
+            self.last = value;
+        }
+    }
+
+    fn feed() -> ByteFeed<Count> {
+        ByteFeed::new(Count { values: 0, last: 0 })
+    }
+
+    #[test]
+    fn test_long_write_is_decoded_in_chunks() {
+        let mut feed = feed();
+        let mut buf = [0u8; 1600];
+        for (i, chunk) in buf.chunks_exact_mut(4).enumerate() {
+            chunk[3] = i as u8;
+        }
+        assert_eq!(feed.write_no_std(&buf), 400);
+        assert_eq!((feed.inner().values, feed.inner().last), (400, 399 % 256));
+    }
+
+    #[test]
+    fn test_short_writes() {
+        let mut feed = feed();
+        assert_eq!(feed.write_no_std(&[0, 0, 0, 5]), 1);
+        assert_eq!(feed.inner().last, 5);
+        // value split over writes shorter than one value
+        assert_eq!(feed.write_no_std(&[0, 0]), 0);
+        assert_eq!(feed.write_no_std(&[0]), 0);
+        assert_eq!(feed.write_no_std(&[7, 0, 0]), 1);
+        assert_eq!(feed.write_no_std(&[0, 8]), 1);
+        assert_eq!((feed.inner().values, feed.inner().last), (3, 8));
+    }
+
+    struct Collect {
+        values: [i32; 4],
+        len: usize,
+    }
+
+    impl Push for Collect {
+        fn push(&mut self, value: i32) {
+            self.values[self.len] = value;
+            self.len += 1;
+        }
+    }
+
+    fn collect(byte_order: ByteOrder, writes: &[&[u8]]) -> ([i32; 4], usize) {
+        let mut feed = ByteFeed::with_byte_order(
+            Collect {
+                values: [0; 4],
+                len: 0,
+            },
+            byte_order,
+        );
+        for buf in writes {
+            feed.write_no_std(buf);
+        }
+        (feed.inner().values, feed.inner().len)
+    }
+
+    #[test]
+    fn test_big_endian_negative_values() {
+        let mut buf = [0u8; 16];
+        for (chunk, value) in buf.chunks_exact_mut(4).zip([-2, 5, -300, 7]) {
+            chunk.copy_from_slice(&i32::to_be_bytes(value));
+        }
+        assert_eq!(collect(ByteOrder::Big, &[&buf]), ([-2, 5, -300, 7], 4));
+        // short writes don`t change the order either
+        assert_eq!(
+            collect(ByteOrder::Big, &[&buf[..4], &buf[4..6], &buf[6..]]),
+            ([-2, 5, -300, 7], 4)
+        );
+    }
+
+    #[test]
+    fn test_byte_order_is_not_guessed() {
+        let mut buf = [0u8; 12];
+        for (chunk, value) in buf.chunks_exact_mut(4).zip([16777216, 1, 2]) {
+            chunk.copy_from_slice(&i32::to_be_bytes(value));
+        }
+        assert_eq!(collect(ByteOrder::Big, &[&buf]), ([16777216, 1, 2, 0], 3));
+
+        for (chunk, value) in buf.chunks_exact_mut(4).zip([-2, 16777216, -300]) {
+            chunk.copy_from_slice(&i32::to_le_bytes(value));
+        }
+        assert_eq!(
+            collect(ByteOrder::Little, &[&buf]),
+            ([-2, 16777216, -300, 0], 3)
+        );
+    }
+}
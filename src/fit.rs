@@ -0,0 +1,225 @@
+use rand::Rng;
+use rand_distr::{Distribution, Exp, LogNormal, Normal, Uniform};
+
+use crate::special::normal_cdf;
+
+// Distribution family with parameters estimated from the window by maximum likelihood.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FittedDistribution {
+    Normal { mean: f32, std_dev: f32 },
+    Uniform { min: f32, max: f32 },
+    Exponential { rate: f32 },
+    // mu and sigma of ln(value)
+    LogNormal { mu: f32, sigma: f32 },
+}
+
+impl FittedDistribution {
+    pub fn cdf(&self, x: f64) -> f64 {
+        match *self {
+            FittedDistribution::Normal { mean, std_dev } => {
+                normal_cdf((x - mean as f64) / std_dev as f64)
+            }
+            FittedDistribution::Uniform { min, max } => {
+                ((x - min as f64) / (max as f64 - min as f64)).clamp(0.0, 1.0)
+            }
+            FittedDistribution::Exponential { rate } => {
+                if x <= 0.0 {
+                    0.0
+                } else {
+                    1.0 - (-(rate as f64) * x).exp()
+                }
+            }
+            FittedDistribution::LogNormal { mu, sigma } => {
+                if x <= 0.0 {
+                    0.0
+                } else {
+                    normal_cdf((x.ln() - mu as f64) / sigma as f64)
+                }
+            }
+        }
+    }
+
+    // draw one value, parameters were checked by fit so sampling can`t fail
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> f32 {
+        match *self {
+            FittedDistribution::Normal { mean, std_dev } => Normal::new(mean, std_dev)
+                .map(|d| d.sample(rng))
+                .unwrap_or(mean),
+            FittedDistribution::Uniform { min, max } => {
+                Uniform::new_inclusive(min, max).sample(rng)
+            }
+            FittedDistribution::Exponential { rate } => {
+                Exp::new(rate).map(|d| d.sample(rng)).unwrap_or(0.0)
+            }
+            FittedDistribution::LogNormal { mu, sigma } => LogNormal::new(mu, sigma)
+                .map(|d| d.sample(rng))
+                .unwrap_or(0.0),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FitReport {
+    pub distribution: FittedDistribution,
+    // largest distance between empirical and fitted cdf
+    pub ks_statistic: f32,
+    // asymptotic Kolmogorov p-value. Parameters come from the same values, so it is too optimistic (Lilliefors)
+    // and is meant for comparing families rather than as an exact test.
+    pub ks_p_value: f32,
+}
+
+// Fits of all families which are possible for the values. Exponential needs values >= 0, log-normal values > 0.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FitSummary {
+    pub normal: Option<FitReport>,
+    pub uniform: Option<FitReport>,
+    pub exponential: Option<FitReport>,
+    pub log_normal: Option<FitReport>,
+    // Jarque-Bera normality statistic n / 6 * (skewness^2 + excess_kurtosis^2 / 4)
+    pub jarque_bera: f32,
+    // chi-squared with 2 degrees of freedom, small p-value means values are not normal
+    pub jarque_bera_p_value: f32,
+}
+
+impl FitSummary {
+    // family with the smallest KS statistic
+    pub fn best(&self) -> Option<FitReport> {
+        [self.normal, self.uniform, self.exponential, self.log_normal]
+            .into_iter()
+            .flatten()
+            .min_by(|a, b| a.ks_statistic.total_cmp(&b.ks_statistic))
+    }
+}
+
+// Fit normal, uniform, exponential and log-normal distribution to values and measure goodness of fit.
+// Empirical cdf is counted for every value without sorting, so it is O(n^2) and needs no extra memory.
+// None for less than 3 values or constant values.
+pub fn fit(values: &[i32]) -> Option<FitSummary> {
+    let n = values.len() as f64;
+    if values.len() < 3 {
+        return None;
+    }
+    let mean = values.iter().map(|v| *v as f64).sum::<f64>() / n;
+    let central_moment = |power: i32| {
+        values
+            .iter()
+            .map(|v| (*v as f64 - mean).powi(power))
+            .sum::<f64>()
+            / n
+    };
+    let variance = central_moment(2);
+    if variance <= 0.0 {
+        return None;
+    }
+    let skewness = central_moment(3) / variance.powf(1.5);
+    let excess_kurtosis = central_moment(4) / (variance * variance) - 3.0;
+    let jarque_bera = n / 6.0 * (skewness * skewness + excess_kurtosis * excess_kurtosis / 4.0);
+
+    let min = values.iter().copied().min().unwrap_or(0);
+    let max = values.iter().copied().max().unwrap_or(0);
+
+    let normal = FittedDistribution::Normal {
+        mean: mean as f32,
+        std_dev: variance.sqrt() as f32,
+    };
+    let uniform = FittedDistribution::Uniform {
+        min: min as f32,
+        max: max as f32,
+    };
+    let exponential = (min >= 0).then(|| FittedDistribution::Exponential {
+        rate: (1.0 / mean) as f32,
+    });
+    let log_normal = if min > 0 {
+        let ln_mean = values.iter().map(|v| (*v as f64).ln()).sum::<f64>() / n;
+        let ln_variance = values
+            .iter()
+            .map(|v| ((*v as f64).ln() - ln_mean).powi(2))
+            .sum::<f64>()
+            / n;
+        (ln_variance > 0.0).then(|| FittedDistribution::LogNormal {
+            mu: ln_mean as f32,
+            sigma: ln_variance.sqrt() as f32,
+        })
+    } else {
+        None
+    };
+
+    Some(FitSummary {
+        normal: Some(goodness_of_fit(values, normal)),
+        uniform: Some(goodness_of_fit(values, uniform)),
+        exponential: exponential.map(|d| goodness_of_fit(values, d)),
+        log_normal: log_normal.map(|d| goodness_of_fit(values, d)),
+        jarque_bera: jarque_bera as f32,
+        jarque_bera_p_value: (-jarque_bera / 2.0).exp() as f32,
+    })
+}
+
+fn goodness_of_fit(values: &[i32], distribution: FittedDistribution) -> FitReport {
+    let n = values.len() as f64;
+    let mut ks: f64 = 0.0;
+    for x in values {
+        let below = values.iter().filter(|v| *v < x).count() as f64;
+        let at_or_below = values.iter().filter(|v| *v <= x).count() as f64;
+        let cdf = distribution.cdf(*x as f64);
+        // empirical cdf jumps at x, both sides of the step are compared
+        ks = ks
+            .max((at_or_below / n - cdf).abs())
+            .max((cdf - below / n).abs());
+    }
+    FitReport {
+        distribution,
+        ks_statistic: ks as f32,
+        ks_p_value: kolmogorov_p_value(ks, n) as f32,
+    }
+}
+
+// P(D > d) for n values, Stephens` approximation of the Kolmogorov distribution
+fn kolmogorov_p_value(d: f64, n: f64) -> f64 {
+    let lambda = (n.sqrt() + 0.12 + 0.11 / n.sqrt()) * d;
+    if lambda < 0.2 {
+        return 1.0;
+    }
+    let mut sum = 0.0;
+    let mut sign = 1.0;
+    for k in 1..=100 {
+        let k = k as f64;
+        let term = (-2.0 * k * k * lambda * lambda).exp();
+        sum += sign * term;
+        if term < 1e-12 {
+            break;
+        }
+        sign = -sign;
+    }
+    (2.0 * sum).clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit_families() {
+        let summary = fit(&[1, 1, 1, 2, 2, 3, 4, 6, 9, 15]).unwrap();
+        assert_eq!(
+            summary.exponential.map(|f| f.distribution),
+            Some(FittedDistribution::Exponential { rate: 0.227_272_73 })
+        );
+        assert!((summary.normal.unwrap().ks_statistic - 0.237_108).abs() < 1e-5);
+        assert!((summary.jarque_bera - 3.883_42).abs() < 1e-4);
+        // skewed values fit log-normal better than normal
+        let best = summary.best().unwrap();
+        assert!(matches!(
+            best.distribution,
+            FittedDistribution::LogNormal { .. }
+        ));
+        assert!((best.ks_statistic - 0.177_083).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_families_need_positive_values() {
+        let summary = fit(&[-1, 0, 1]).unwrap();
+        assert_eq!((summary.exponential, summary.log_normal), (None, None));
+        assert_eq!(summary.uniform.unwrap().ks_statistic, 1.0 / 3.0);
+        assert_eq!(fit(&[2, 2, 2]), None);
+    }
+}
@@ -0,0 +1,321 @@
+use crate::byte_converter::LOG;
+use crate::logs::Logger;
+use crate::special::normal_quantile;
+
+// highest order of AR model, normal equations are solved in fixed size arrays
+pub const MAX_AR_ORDER: usize = 8;
+
+// Point forecast with prediction interval, bounds assume normally distributed residuals.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Forecast {
+    pub value: f32,
+    pub lower: f32,
+    pub upper: f32,
+}
+
+fn interval(value: f64, std_error: f64, confidence: f32) -> Option<Forecast> {
+    if !(confidence > 0.0 && confidence < 1.0) {
+        LOG.warn("confidence has to be in (0, 1) range");
+        return None;
+    }
+    let half_width = normal_quantile(1.0 - (1.0 - confidence as f64) / 2.0) * std_error;
+    Some(Forecast {
+        value: value as f32,
+        lower: (value - half_width) as f32,
+        upper: (value + half_width) as f32,
+    })
+}
+
+// Holt`s linear (double exponential) smoothing fitted over values from the oldest to the newest.
+// alpha smooths the level and beta the trend, both in (0, 1].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HoltModel {
+    level: f64,
+    trend: f64,
+    alpha: f64,
+    beta: f64,
+    // standard deviation of one step ahead errors over the values
+    residual_std: f64,
+}
+
+impl HoltModel {
+    // None for less than 3 values
+    pub fn fit(values: &[i32], alpha: f32, beta: f32) -> Option<HoltModel> {
+        let alpha = checked_smoothing(alpha, 0.5);
+        let beta = checked_smoothing(beta, 0.1);
+        let (first, second) = match values {
+            [first, second, _, ..] => (*first as f64, *second as f64),
+            _ => return None,
+        };
+        let mut level = first;
+        let mut trend = second - first;
+        let mut squares = 0.0;
+        for value in values.iter().skip(1) {
+            let value = *value as f64;
+            let predicted = level + trend;
+            squares += (value - predicted) * (value - predicted);
+            let previous_level = level;
+            level = alpha * value + (1.0 - alpha) * predicted;
+            trend = beta * (level - previous_level) + (1.0 - beta) * trend;
+        }
+        // first error is zero by construction of the initial trend
+        let residual_std = (squares / (values.len() - 2) as f64).sqrt();
+        Some(HoltModel {
+            level,
+            trend,
+            alpha,
+            beta,
+            residual_std,
+        })
+    }
+
+    pub fn level(&self) -> f32 {
+        self.level as f32
+    }
+
+    pub fn trend(&self) -> f32 {
+        self.trend as f32
+    }
+
+    pub fn residual_std(&self) -> f32 {
+        self.residual_std as f32
+    }
+
+    // forecast horizon steps after the newest value (1 is the next value), None for horizon 0
+    pub fn forecast(&self, horizon: usize, confidence: f32) -> Option<Forecast> {
+        if horizon == 0 {
+            return None;
+        }
+        let value = self.level + horizon as f64 * self.trend;
+        // variance of additive trend model, errors of the earlier steps are carried into level and trend
+        let variance_factor: f64 = 1.0
+            + (1..horizon)
+                .map(|j| {
+                    let weight = self.alpha * (1.0 + j as f64 * self.beta);
+                    weight * weight
+                })
+                .sum::<f64>();
+        interval(
+            value,
+            self.residual_std * variance_factor.sqrt(),
+            confidence,
+        )
+    }
+}
+
+fn checked_smoothing(value: f32, default: f64) -> f64 {
+    if value > 0.0 && value <= 1.0 {
+        value as f64
+    } else {
+        LOG.warn("smoothing parameter has to be in (0, 1] range, using default");
+        default
+    }
+}
+
+// Autoregressive model value = intercept + sum(coefficient[i] * value[t - 1 - i]) fitted by least squares over
+// values from the oldest to the newest.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ArModel {
+    order: usize,
+    intercept: f64,
+    coefficients: [f64; MAX_AR_ORDER],
+    // newest values, newest first
+    recent: [f64; MAX_AR_ORDER],
+    residual_std: f64,
+}
+
+impl ArModel {
+    // order in 1..=MAX_AR_ORDER. None when there are not more equations than parameters plus one
+    // or values are degenerate (e.g. constant).
+    pub fn fit(values: &[i32], order: usize) -> Option<ArModel> {
+        if order == 0 || order > MAX_AR_ORDER {
+            LOG.warn("order of AR model has to be in 1..=MAX_AR_ORDER range");
+            return None;
+        }
+        let equations = values.len().checked_sub(order)?;
+        let parameters = order + 1;
+        if equations <= parameters {
+            return None;
+        }
+
+        // normal equations X^T X b = X^T y, row of X is (1, value[t - 1], ..., value[t - order])
+        let mut matrix = [[0.0f64; MAX_AR_ORDER + 2]; MAX_AR_ORDER + 1];
+        let regressors = |t: usize, i: usize| -> f64 {
+            if i == 0 {
+                1.0
+            } else {
+                values[t - i] as f64
+            }
+        };
+        for (t, y) in values.iter().enumerate().skip(order) {
+            let y = *y as f64;
+            for (i, row) in matrix.iter_mut().take(parameters).enumerate() {
+                let x_i = regressors(t, i);
+                for (j, cell) in row.iter_mut().take(parameters).enumerate() {
+                    *cell += x_i * regressors(t, j);
+                }
+                // augmented column
+                row[parameters] += x_i * y;
+            }
+        }
+        let solution = solve(&mut matrix, parameters)?;
+
+        let mut squares = 0.0;
+        for (t, y) in values.iter().enumerate().skip(order) {
+            let predicted: f64 = (0..parameters)
+                .map(|i| solution[i] * regressors(t, i))
+                .sum();
+            let error = *y as f64 - predicted;
+            squares += error * error;
+        }
+        let mut coefficients = [0.0; MAX_AR_ORDER];
+        coefficients[..order].copy_from_slice(&solution[1..parameters]);
+        let mut recent = [0.0; MAX_AR_ORDER];
+        for (slot, value) in recent.iter_mut().zip(values.iter().rev()).take(order) {
+            *slot = *value as f64;
+        }
+        Some(ArModel {
+            order,
+            intercept: solution[0],
+            coefficients,
+            recent,
+            residual_std: (squares / (equations - parameters) as f64).sqrt(),
+        })
+    }
+
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    pub fn intercept(&self) -> f32 {
+        self.intercept as f32
+    }
+
+    // coefficient of the value i + 1 steps back
+    pub fn coefficient(&self, i: usize) -> Option<f32> {
+        self.coefficients[..self.order].get(i).map(|c| *c as f32)
+    }
+
+    pub fn residual_std(&self) -> f32 {
+        self.residual_std as f32
+    }
+
+    // forecast horizon steps after the newest value (1 is the next value), None for horizon 0
+    pub fn forecast(&self, horizon: usize, confidence: f32) -> Option<Forecast> {
+        if horizon == 0 {
+            return None;
+        }
+        let p = self.order;
+        // forecasts replace unknown values, psi are weights of past errors in the forecast error
+        let mut recent = self.recent;
+        let mut psi = [0.0f64; MAX_AR_ORDER];
+        psi[0] = 1.0;
+        let mut psi_squares = 0.0;
+        let mut value = 0.0;
+        for step in 0..horizon {
+            value = self.intercept
+                + (0..p)
+                    .map(|i| self.coefficients[i] * recent[i])
+                    .sum::<f64>();
+            recent.copy_within(0..p - 1, 1);
+            recent[0] = value;
+
+            // psi[0] is weight of the current step, older weights follow
+            psi_squares += psi[0] * psi[0];
+            let next_psi: f64 = (0..p.min(step + 1))
+                .map(|i| self.coefficients[i] * psi[i])
+                .sum();
+            psi.copy_within(0..p - 1, 1);
+            psi[0] = next_psi;
+        }
+        interval(value, self.residual_std * psi_squares.sqrt(), confidence)
+    }
+}
+
+// Gauss-Jordan elimination with partial pivoting of n x (n + 1) augmented matrix
+fn solve(
+    matrix: &mut [[f64; MAX_AR_ORDER + 2]; MAX_AR_ORDER + 1],
+    n: usize,
+) -> Option<[f64; MAX_AR_ORDER + 1]> {
+    for column in 0..n {
+        let pivot = (column..n).max_by(|a, b| {
+            matrix[*a][column]
+                .abs()
+                .total_cmp(&matrix[*b][column].abs())
+        })?;
+        if matrix[pivot][column].abs() < 1e-9 {
+            return None;
+        }
+        matrix.swap(column, pivot);
+        let pivot_row = matrix[column];
+        for (row, cells) in matrix.iter_mut().enumerate().take(n) {
+            if row != column {
+                let factor = cells[column] / pivot_row[column];
+                for (cell, pivot) in cells
+                    .iter_mut()
+                    .zip(pivot_row.iter())
+                    .take(n + 1)
+                    .skip(column)
+                {
+                    *cell -= factor * pivot;
+                }
+            }
+        }
+    }
+    let mut solution = [0.0; MAX_AR_ORDER + 1];
+    for (i, x) in solution.iter_mut().enumerate().take(n) {
+        *x = matrix[i][n] / matrix[i][i];
+    }
+    Some(solution)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_holt_follows_line() {
+        let model = HoltModel::fit(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10], 0.5, 0.5).unwrap();
+        assert_eq!((model.level(), model.trend()), (10.0, 1.0));
+        assert_eq!(model.residual_std(), 0.0);
+        let forecast = model.forecast(3, 0.95).unwrap();
+        assert_eq!(
+            (forecast.value, forecast.lower, forecast.upper),
+            (13.0, 13.0, 13.0)
+        );
+        assert_eq!(model.forecast(0, 0.95), None);
+        assert_eq!(HoltModel::fit(&[1, 2], 0.5, 0.5), None);
+    }
+
+    #[test]
+    fn test_holt_interval_widens() {
+        let model = HoltModel::fit(&[10, 12, 11, 14, 13, 15, 17, 16], 0.5, 0.3).unwrap();
+        let next = model.forecast(1, 0.9).unwrap();
+        let later = model.forecast(4, 0.9).unwrap();
+        assert!(next.lower < next.value && next.value < next.upper);
+        assert!(later.upper - later.lower > next.upper - next.lower);
+    }
+
+    #[test]
+    fn test_ar_exact_decay() {
+        let model = ArModel::fit(&[64, 32, 16, 8, 4, 2], 1).unwrap();
+        assert!((model.coefficient(0).unwrap() - 0.5).abs() < 1e-6);
+        assert!(model.intercept().abs() < 1e-6);
+        assert_eq!(model.coefficient(1), None);
+        let forecast = model.forecast(2, 0.95).unwrap();
+        assert!((forecast.value - 0.5).abs() < 1e-5);
+        assert!(ArModel::fit(&[1, 2, 3], 1).is_none());
+        assert!(ArModel::fit(&[5, 5, 5, 5, 5, 5], 2).is_none());
+    }
+
+    #[test]
+    fn test_ar_interval_widens() {
+        let values = [3, 5, 4, 6, 5, 7, 4, 6, 5, 3, 4, 6, 7, 5, 4, 5];
+        let model = ArModel::fit(&values, 2).unwrap();
+        assert!(model.residual_std() > 0.0);
+        let next = model.forecast(1, 0.95).unwrap();
+        let later = model.forecast(5, 0.95).unwrap();
+        assert!(later.upper - later.lower >= next.upper - next.lower);
+        assert!((next.upper - next.lower - 2.0 * 1.959_964 * model.residual_std()).abs() < 1e-4);
+    }
+}
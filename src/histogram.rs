@@ -0,0 +1,226 @@
+use crate::byte_converter::LOG;
+use crate::feed::{Evict, Push};
+use crate::logs::Logger;
+use crate::order_stats::QuantileSummary;
+use crate::ring_buffer::RingBuffer;
+
+// Rolling histogram with B equally wide bins between min (inclusive) and max (exclusive) plus underflow and
+// overflow bin. Window keeps only the slot of each value (u16), so evicting just decrements its counter and the
+// whole histogram has fixed size.
+pub struct RollingHistogram<const B: usize, const N: usize> {
+    window: RingBuffer<u16, N>,
+    // counts of regular bins
+    counts: [u32; B],
+    underflow: u32,
+    overflow: u32,
+    min: i32,
+    max: i32,
+}
+
+// slot stored in the window, regular bins are 1..=B
+const UNDERFLOW_SLOT: u16 = 0;
+
+impl<const B: usize, const N: usize> RollingHistogram<B, N> {
+    pub fn new(min: i32, max: i32, window_size: usize) -> Self {
+        if B == 0 || B > u16::MAX as usize - 2 {
+            LOG.warn(
+                "histogram needs 1..=65533 bins, all values will end in underflow or overflow",
+            );
+        }
+        let max = if max > min {
+            max
+        } else {
+            LOG.warn("histogram max has to be bigger than min. Using bins 1 wide");
+            min.saturating_add(B.max(1) as i32)
+        };
+        Self {
+            window: RingBuffer::new(window_size),
+            counts: [0; B],
+            underflow: 0,
+            overflow: 0,
+            min,
+            max,
+        }
+    }
+
+    fn width(&self) -> f64 {
+        (self.max as f64 - self.min as f64) / B as f64
+    }
+
+    fn slot(&self, value: i32) -> u16 {
+        if value < self.min || B == 0 || B > u16::MAX as usize - 2 {
+            UNDERFLOW_SLOT
+        } else if value >= self.max {
+            B as u16 + 1
+        } else {
+            let bin = ((value as f64 - self.min as f64) / self.width()) as usize;
+            // rounding can`t move value outside of regular bins
+            bin.min(B - 1) as u16 + 1
+        }
+    }
+
+    fn counter(&mut self, slot: u16) -> Option<&mut u32> {
+        match slot {
+            UNDERFLOW_SLOT => Some(&mut self.underflow),
+            s if s as usize == B + 1 => Some(&mut self.overflow),
+            s => self.counts.get_mut(s as usize - 1),
+        }
+    }
+
+    pub fn push_value(&mut self, value: i32) {
+        let slot = self.slot(value);
+        if let Some(evicted) = self.window.push(slot) {
+            if let Some(counter) = self.counter(evicted) {
+                *counter -= 1;
+            }
+        }
+        if let Some(counter) = self.counter(slot) {
+            *counter += 1;
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.window.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.window.is_empty()
+    }
+
+    // counts of regular bins
+    pub fn counts(&self) -> &[u32; B] {
+        &self.counts
+    }
+
+    // values smaller than min
+    pub fn underflow(&self) -> u32 {
+        self.underflow
+    }
+
+    // values bigger or equal to max
+    pub fn overflow(&self) -> u32 {
+        self.overflow
+    }
+
+    // [lower, upper) edges of the regular bin
+    pub fn bin_range(&self, bin: usize) -> Option<(f32, f32)> {
+        if bin >= B {
+            return None;
+        }
+        let lower = self.min as f64 + bin as f64 * self.width();
+        Some((lower as f32, (lower + self.width()) as f32))
+    }
+
+    // probability density in the regular bin, so the sum of pdf * width over all bins plus under/overflow is 1
+    pub fn pdf(&self, bin: usize) -> Option<f32> {
+        let count = *self.counts.get(bin)?;
+        if self.window.is_empty() {
+            return Some(0.0);
+        }
+        Some((count as f64 / self.window.len() as f64 / self.width()) as f32)
+    }
+
+    // fraction of values smaller than upper edge of the regular bin, underflow included
+    pub fn cdf(&self, bin: usize) -> Option<f32> {
+        let below = self.counts.get(..=bin)?.iter().sum::<u32>() + self.underflow;
+        if self.window.is_empty() {
+            return Some(0.0);
+        }
+        Some((below as f64 / self.window.len() as f64) as f32)
+    }
+
+    // quantile estimated from bin counts, value is linearly interpolated inside the bin.
+    // Quantiles falling into underflow or overflow are reported as min or max.
+    pub fn quantile_estimate(&self, q: f32) -> Option<f32> {
+        if self.window.is_empty() {
+            return None;
+        }
+        if !(0.0..=1.0).contains(&q) {
+            LOG.warn("quantile has to be in 0..=1 range");
+            return None;
+        }
+        let target = q as f64 * self.window.len() as f64;
+        let mut below = self.underflow as f64;
+        if target <= below && self.underflow > 0 {
+            return Some(self.min as f32);
+        }
+        for (bin, count) in self.counts.iter().enumerate() {
+            let count = *count as f64;
+            if count > 0.0 && target <= below + count {
+                let lower = self.min as f64 + bin as f64 * self.width();
+                return Some((lower + (target - below) / count * self.width()) as f32);
+            }
+            below += count;
+        }
+        Some(self.max as f32)
+    }
+}
+
+impl<const B: usize, const N: usize> Push for RollingHistogram<B, N> {
+    fn push(&mut self, value: i32) {
+        self.push_value(value);
+    }
+}
+
+impl<const B: usize, const N: usize> Evict for RollingHistogram<B, N> {
+    fn evict_oldest(&mut self) -> bool {
+        match self.window.pop_oldest() {
+            Some(evicted) => {
+                if let Some(counter) = self.counter(evicted) {
+                    *counter -= 1;
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn window_len(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<const B: usize, const N: usize> QuantileSummary for RollingHistogram<B, N> {
+    fn quantile(&mut self, q: f32) -> Option<f32> {
+        self.quantile_estimate(q)
+    }
+
+    fn count(&self) -> u64 {
+        self.window.len() as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_follow_window() {
+        let mut hist = RollingHistogram::<4, 8>::new(0, 40, 4);
+        for value in [-5, 0, 15, 39] {
+            hist.push_value(value);
+        }
+        assert_eq!(hist.counts(), &[1, 1, 0, 1]);
+        assert_eq!(hist.underflow(), 1);
+        hist.push_value(40); // -5 is evicted
+        hist.push_value(12); // 0 is evicted
+        assert_eq!(hist.underflow(), 0);
+        assert_eq!(hist.overflow(), 1);
+        assert_eq!(hist.counts(), &[0, 2, 0, 1]);
+        assert_eq!(hist.bin_range(1), Some((10.0, 20.0)));
+    }
+
+    #[test]
+    fn test_pdf_cdf_and_quantile() {
+        let mut hist = RollingHistogram::<2, 8>::new(0, 10, 8);
+        for value in [1, 2, 3, 6] {
+            hist.push_value(value);
+        }
+        assert_eq!(hist.pdf(0), Some(0.15));
+        assert_eq!(hist.cdf(0), Some(0.75));
+        assert_eq!(hist.cdf(1), Some(1.0));
+        assert_eq!(hist.pdf(2), None);
+        // half of the values is in the first bin, 2 of its 3 values
+        assert_eq!(hist.quantile_estimate(0.5), Some(10.0 / 3.0));
+    }
+}
@@ -0,0 +1,128 @@
+use crate::special::{incomplete_beta, normal_cdf};
+use crate::sums::ExactSums;
+
+// Count, mean and sample variance of a window, enough for Welch's t-test. Can be stored as a baseline.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SampleSummary {
+    pub count: usize,
+    pub mean: f32,
+    // sample variance, divided by count - 1
+    pub variance: f32,
+}
+
+impl SampleSummary {
+    pub fn from_values(values: &[i32]) -> SampleSummary {
+        let sums = ExactSums::from_values(values);
+        SampleSummary {
+            count: values.len(),
+            mean: sums.mean().unwrap_or(0.0) as f32,
+            variance: sums.sample_variance().unwrap_or(0.0) as f32,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TestResult {
+    // t for Welch's test, U of the first sample for Mann-Whitney
+    pub statistic: f32,
+    // Welch-Satterthwaite degrees of freedom, None for tests without them
+    pub degrees_of_freedom: Option<f32>,
+    // two sided p-value
+    pub p_value: f32,
+}
+
+// Welch's t-test of equal means, variances don`t have to be equal.
+// None when a sample has less than 2 values or both are constant.
+pub fn welch_t_test(a: &SampleSummary, b: &SampleSummary) -> Option<TestResult> {
+    if a.count < 2 || b.count < 2 {
+        return None;
+    }
+    let error_a = a.variance as f64 / a.count as f64;
+    let error_b = b.variance as f64 / b.count as f64;
+    let error = error_a + error_b;
+    if error <= 0.0 {
+        return None;
+    }
+    let t = (a.mean as f64 - b.mean as f64) / error.sqrt();
+    let df = error * error
+        / (error_a * error_a / (a.count - 1) as f64 + error_b * error_b / (b.count - 1) as f64);
+    // two sided tail of Student t distribution
+    let p_value = incomplete_beta(df / 2.0, 0.5, df / (df + t * t));
+    Some(TestResult {
+        statistic: t as f32,
+        degrees_of_freedom: Some(df as f32),
+        p_value: p_value as f32,
+    })
+}
+
+// Mann-Whitney U test, whether values of one sample tend to be larger than values of the other. Normal
+// approximation with tie and continuity correction, so it needs about 8 or more values in each sample.
+// Compares every pair without sorting or extra memory, O(a.len() * b.len()). None for empty sample or when all
+// values are equal.
+pub fn mann_whitney_u(a: &[i32], b: &[i32]) -> Option<TestResult> {
+    if a.is_empty() || b.is_empty() {
+        return None;
+    }
+    let (n_a, n_b) = (a.len() as f64, b.len() as f64);
+    let mut u = 0.0;
+    for x in a {
+        for y in b {
+            if x > y {
+                u += 1.0;
+            } else if x == y {
+                u += 0.5;
+            }
+        }
+    }
+
+    // every value in a group of t ties adds t^2 - 1, so the group adds t^3 - t
+    let n = n_a + n_b;
+    let ties: f64 = a
+        .iter()
+        .chain(b.iter())
+        .map(|x| {
+            let t = a.iter().chain(b.iter()).filter(|y| *y == x).count() as f64;
+            t * t - 1.0
+        })
+        .sum();
+    let variance = n_a * n_b / 12.0 * ((n + 1.0) - ties / (n * (n - 1.0)));
+    if variance <= 0.0 {
+        return None;
+    }
+    let diff = u - n_a * n_b / 2.0;
+    let corrected = (diff.abs() - 0.5).max(0.0);
+    let z = corrected / variance.sqrt();
+    Some(TestResult {
+        statistic: u as f32,
+        degrees_of_freedom: None,
+        p_value: (2.0 * normal_cdf(-z)).min(1.0) as f32,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_welch() {
+        let a = SampleSummary::from_values(&[1, 2, 3, 4, 5]);
+        let b = SampleSummary::from_values(&[3, 4, 5, 6, 7]);
+        assert_eq!((a.mean, a.variance), (3.0, 2.5));
+        let result = welch_t_test(&a, &b).unwrap();
+        assert_eq!(result.statistic, -2.0);
+        assert_eq!(result.degrees_of_freedom, Some(8.0));
+        assert!((result.p_value - 0.080_516).abs() < 1e-5);
+        assert_eq!(welch_t_test(&a, &SampleSummary::from_values(&[1])), None);
+    }
+
+    #[test]
+    fn test_mann_whitney() {
+        let result = mann_whitney_u(&[1, 2, 3, 4, 5], &[6, 7, 8, 9, 10]).unwrap();
+        assert_eq!(result.statistic, 0.0);
+        assert!((result.p_value - 0.012_185).abs() < 1e-5);
+        // same values give no evidence of difference
+        let result = mann_whitney_u(&[1, 2, 3], &[3, 2, 1]).unwrap();
+        assert_eq!((result.statistic, result.p_value), (4.5, 1.0));
+        assert_eq!(mann_whitney_u(&[4, 4], &[4]), None);
+    }
+}
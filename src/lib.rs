@@ -1,10 +1,38 @@
 #![no_std]
-use byte_converter::ByteConverter;
-use rand::thread_rng;
+use byte_converter::{ByteConverter, FixedArray};
+use rand::{thread_rng, Rng};
 use rand_distr::{Distribution, Normal};
 
+mod adwin;
+mod anomaly;
+mod batch_window;
 mod byte_converter;
+mod capability;
+mod change_point;
+mod drift;
+mod ewma;
+mod feed;
+mod fit;
+mod forecast;
+mod histogram;
+mod hypothesis;
+mod log_histogram;
 mod logs;
+mod min_max;
+mod moments;
+mod order_stats;
+mod paired;
+mod resample;
+mod reservoir;
+mod ring_buffer;
+mod spc;
+mod special;
+mod sums;
+mod tdigest;
+mod time_window;
+mod trend;
+mod vector_stats;
+mod watermark;
 #[cfg(feature = "std")]
 use crate::byte_converter::LOG;
 
@@ -12,10 +40,47 @@ use crate::byte_converter::LOG;
 use crate::byte_converter::LOG;
 
 use crate::logs::Logger;
+use crate::special::{normal_quantile, student_t_quantile};
+
+pub use crate::adwin::AdaptiveWindow;
+pub use crate::anomaly::{Anomaly, ZScoreDetector};
+pub use crate::batch_window::{HoppingWindow, SessionWindow, TumblingWindow, WindowSummary};
+pub use crate::capability::Capability;
+pub use crate::change_point::{ChangeDetector, ChangePoint, Cusum, Direction, PageHinkley};
+pub use crate::drift::{Baseline, DriftMonitor, DriftReport, DriftThresholds};
+pub use crate::ewma::Ewma;
+pub use crate::feed::{ByteFeed, ByteOrder, Evict, Push};
+pub use crate::fit::{fit, FitReport, FitSummary, FittedDistribution};
+pub use crate::forecast::{ArModel, Forecast, HoltModel, MAX_AR_ORDER};
+pub use crate::histogram::RollingHistogram;
+pub use crate::hypothesis::{mann_whitney_u, welch_t_test, SampleSummary, TestResult};
+pub use crate::log_histogram::{
+    log_histogram_len, HistogramError, LogHistogram, MAX_SIGNIFICANT_DIGITS,
+};
+pub use crate::min_max::RollingMinMax;
+pub use crate::moments::RollingMoments;
+pub use crate::order_stats::{QuantileSummary, RollingQuantiles};
+pub use crate::paired::PairedRollingStats;
+pub use crate::resample::{
+    bootstrap_ci, empirical_sample, silverman_bandwidth, smoothed_sample, Statistic,
+};
+pub use crate::reservoir::{Reservoir, ReservoirAlgorithm};
+pub use crate::ring_buffer::RingBuffer;
+pub use crate::spc::{ControlChart, SpcRule, SpcViolation};
+pub use crate::tdigest::TDigest;
+#[cfg(feature = "std")]
+pub use crate::time_window::StdClock;
+pub use crate::time_window::{Clock, TickClock, TimeEvicted, TimeWindow};
+pub use crate::trend::{RollingTrend, Trend, TREND_CONFIDENCE};
+pub use crate::vector_stats::{VectorRollingStats, AXES};
+pub use crate::watermark::EventTimeWindow;
 
 #[cfg(feature = "std")]
 extern crate std;
 
+// from this many values on, confidence intervals use normal quantiles instead of Student t
+pub const LARGE_SAMPLE: usize = 30;
+
 // goal is to save int32 user input until it reach user defined windows size.
 // input should be as sequence of bytes.
 // and each byte can have value -128 up to 255.
@@ -23,7 +88,7 @@ extern crate std;
 // implement lib with std and without rust std -- it means fixed compile size, prefering stack over heap
 // rand_distr can be used without std
 
-struct RollingStats {
+pub struct RollingStats {
     // use byteConverter obejct for handling input
     input_i32: ByteConverter,
     mean: f32,
@@ -31,19 +96,32 @@ struct RollingStats {
     std_dis_samle: f32,
 }
 
+impl Default for RollingStats {
+    fn default() -> Self {
+        Self::new(3)
+    }
+}
+
 impl RollingStats {
-    pub fn default() -> Self {
+    // window of the last window_size values, up to 255 values are kept
+    pub fn new(window_size: usize) -> Self {
+        let window_size = if window_size > 255 {
+            LOG.warn("window size can be at most 255. Using 255");
+            255
+        } else {
+            window_size
+        };
         Self {
-            input_i32: ByteConverter::init(3),
+            input_i32: ByteConverter::init(window_size),
             mean: 0.0,
             std_dev: 0.0,
             std_dis_samle: 0.0,
         }
     }
 
-    // arithmetic mean
-    fn mean(&mut self) -> f32 {
-        if *self.input_i32.get_sum() <= 0 || self.input_i32.get_buf().is_empty() {
+    // arithmetic mean, negative too, same as the centre of mean_ci
+    pub fn mean(&mut self) -> f32 {
+        if self.input_i32.get_buf().is_empty() {
             return 0.0;
         }
         self.mean = *self.input_i32.get_sum() as f32 / self.input_i32.get_buf().len() as f32;
@@ -52,7 +130,7 @@ impl RollingStats {
 
     // standard deviation
     // use to tell us how much each value is far from mean <=> find out how many people are dissconnected from mainstream matrix.
-    fn std_deviation(&mut self) -> f32 {
+    pub fn std_deviation(&mut self) -> f32 {
         // should never reach
         if self.input_i32.get_buf().is_empty() {
             byte_converter::LOG.error("std_deviation can`t be computed from empty buf_current");
@@ -69,7 +147,7 @@ impl RollingStats {
         self.std_dev
     }
 
-    fn std_distribution(&mut self) -> f32 {
+    pub fn std_distribution(&mut self) -> f32 {
         // should never reach
         if self.input_i32.get_buf().is_empty() {
             LOG.error("std_deviation can`t be computed from empty buf_current");
@@ -82,6 +160,191 @@ impl RollingStats {
         self.std_dis_samle
     }
 
+    // Least squares slope of values over their position in the window, with standard error and significance at
+    // TREND_CONFIDENCE. Window is rebuilt by every write, for trend updated on every value use RollingTrend.
+    pub fn trend(&self) -> Option<Trend> {
+        let mut stats = RollingTrend::<255>::new(255);
+        for value in self.chronological_values().iter() {
+            stats.push_value(*value);
+        }
+        stats.trend()
+    }
+
+    // window values from the oldest to the newest, values kept from previous writes come before the fresh ones
+    fn chronological_values(&self) -> FixedArray<i32, 255> {
+        let buf = self.input_i32.get_buf();
+        let fresh = self.input_i32.get_fresh();
+        let older = buf.get(fresh.len()..buf.len()).unwrap_or(&[]);
+        let mut values = FixedArray::new();
+        for value in older.iter().chain(fresh.iter()) {
+            values.push(*value);
+        }
+        values
+    }
+
+    // Holt`s linear smoothing fitted over the window, forecast() gives next values with prediction interval
+    pub fn holt(&self, alpha: f32, beta: f32) -> Option<HoltModel> {
+        let values = self.chronological_values();
+        HoltModel::fit(values.get(0..values.len()).unwrap_or(&[]), alpha, beta)
+    }
+
+    // AR(order) model fitted by least squares over the window
+    pub fn ar(&self, order: usize) -> Option<ArModel> {
+        let values = self.chronological_values();
+        ArModel::fit(values.get(0..values.len()).unwrap_or(&[]), order)
+    }
+
+    // values in the window, decoded by the last write first and values kept from previous writes after them
+    pub fn values(&self) -> &[i32] {
+        let buf = self.input_i32.get_buf();
+        buf.get(0..buf.len()).unwrap_or(&[])
+    }
+
+    // fit normal, uniform, exponential and log-normal distribution to the window, see FitSummary
+    pub fn fit(&self) -> Option<FitSummary> {
+        fit(self.values())
+    }
+
+    // sample from the family which fits the window best, unlike std_distribution it doesn`t assume normal values
+    pub fn fitted_distribution(&self) -> f32 {
+        match self.fit().and_then(|summary| summary.best()) {
+            Some(best) => best.distribution.sample(&mut thread_rng()),
+            None => {
+                LOG.error("distribution can`t be fitted to less than 3 values or constant window");
+                0.0
+            }
+        }
+    }
+
+    // one of the window values picked uniformly, None for empty window
+    pub fn empirical_sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<i32> {
+        empirical_sample(self.values(), rng)
+    }
+
+    // sample from kernel density estimate of the window with Silverman`s bandwidth
+    pub fn smoothed_sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<f32> {
+        smoothed_sample(self.values(), silverman_bandwidth(self.values()), rng)
+    }
+
+    // Percentile bootstrap confidence interval of any statistic of the window from B resamples,
+    // e.g. stats.bootstrap_ci::<_, _, 1000>(|v| Statistic::Median.compute(v), 0.95, &mut rng)
+    pub fn bootstrap_ci<R, S, const B: usize>(
+        &self,
+        statistic: S,
+        confidence: f32,
+        rng: &mut R,
+    ) -> Option<(f32, f32)>
+    where
+        R: Rng + ?Sized,
+        S: FnMut(&mut [i32]) -> f32,
+    {
+        // window never holds more values than ByteConverter buffers
+        bootstrap_ci::<R, S, 255, B>(self.values(), statistic, confidence, rng)
+    }
+
+    // window has as many values as window_size, before that statistics are based on fewer values
+    pub fn is_warmed_up(&self) -> bool {
+        self.values().len() >= self.input_i32.get_window_len()
+    }
+
+    // standard error of the mean, sample std deviation / sqrt(n). None for less than 2 values.
+    pub fn standard_error(&self) -> Option<f32> {
+        let summary = self.summary();
+        if summary.count < 2 {
+            return None;
+        }
+        Some((summary.variance as f64 / summary.count as f64).sqrt() as f32)
+    }
+
+    // (lower, upper) bounds of the window mean for confidence in (0, 1), e.g. 0.95. Student t quantile is used
+    // for less than LARGE_SAMPLE values, normal quantile for more. None for less than 2 values.
+    pub fn mean_ci(&self, confidence: f32) -> Option<(f32, f32)> {
+        if !(confidence > 0.0 && confidence < 1.0) {
+            LOG.warn("confidence has to be in (0, 1) range");
+            return None;
+        }
+        let standard_error = self.standard_error()? as f64;
+        let summary = self.summary();
+        let p = 1.0 - (1.0 - confidence as f64) / 2.0;
+        let quantile = if summary.count < LARGE_SAMPLE {
+            student_t_quantile(p, (summary.count - 1) as f64)
+        } else {
+            normal_quantile(p)
+        };
+        let half_width = quantile * standard_error;
+        let mean = summary.mean as f64;
+        Some(((mean - half_width) as f32, (mean + half_width) as f32))
+    }
+
+    // Cp/Cpk/Pp/Ppk and fraction out of spec of values in the window, see Capability
+    pub fn capability(&self, lsl: f32, usl: f32) -> Option<Capability> {
+        let values = self.chronological_values();
+        Capability::from_values(values.get(0..values.len()).unwrap_or(&[]), lsl, usl)
+    }
+
+    // count, mean and sample variance of the window, e.g. to keep as a baseline for welch_t_test
+    pub fn summary(&self) -> SampleSummary {
+        SampleSummary::from_values(self.values())
+    }
+
+    // Welch's t-test of the window mean against another window (other.summary()) or a stored baseline
+    pub fn welch_t_test(&self, other: &SampleSummary) -> Option<TestResult> {
+        welch_t_test(&self.summary(), other)
+    }
+
+    // Mann-Whitney U test of the window against values of another window or a baseline
+    pub fn mann_whitney(&self, other: &[i32]) -> Option<TestResult> {
+        mann_whitney_u(self.values(), other)
+    }
+
+    // forget the whole window, e.g. after a confirmed change so old level doesn`t pollute new statistics
+    pub fn reset(&mut self) {
+        self.input_i32.reset();
+        self.mean = 0.0;
+        self.std_dev = 0.0;
+        self.std_dis_samle = 0.0;
+    }
+
+    // Run detector over values decoded by the last write and return the last confirmed change. With reset_window
+    // values before the estimated start of the change are dropped from the window.
+    pub fn detect_change<D: ChangeDetector>(
+        &mut self,
+        detector: &mut D,
+        reset_window: bool,
+    ) -> Option<ChangePoint> {
+        let mut last_change = None;
+        let mut keep_from = None;
+        for (i, value) in self.input_i32.get_fresh().iter().enumerate() {
+            if let Some(change) = detector.update(*value) {
+                // start may lie in previous writes, then the whole last write is kept
+                let lag = (change.detected_at - change.start) as usize;
+                keep_from = Some(i.saturating_sub(lag));
+                last_change = Some(change);
+            }
+        }
+        if let (true, Some(from)) = (reset_window, keep_from) {
+            self.input_i32.retain_fresh(from);
+        }
+        last_change
+    }
+
+    // same as write, and every value decoded from buf is pushed into sink too, also values past the window which
+    // the window skips. E.g. Reservoir next to the window samples the whole stream from the same decoded values.
+    pub fn write_through<S: Push>(&mut self, buf: &[u8], sink: &mut S) {
+        #[cfg(feature = "std")]
+        {
+            _ = std::io::Write::write(self, buf);
+        }
+        #[cfg(not(feature = "std"))]
+        self.write_no_std(buf);
+        for value in self.input_i32.get_fresh() {
+            sink.push(*value);
+        }
+        for value in self.input_i32.get_skipped(buf) {
+            sink.push(value);
+        }
+    }
+
     #[cfg(not(feature = "std"))]
     pub fn write_no_std(&mut self, buf: &[u8]) {
         // clear previous statistics
@@ -111,7 +374,7 @@ impl std::io::Write for RollingStats {
         self.std_dis_samle = 0.0;
         self.input_i32.clear_buf();
 
-        if buf.len() > 0 {
+        if !buf.is_empty() {
             // need at leat 2 (bytes) values in first call to do statistics, in second call we need at least 1 byte
             self.input_i32.convert_bytes_to_i32(buf);
         } else {
@@ -139,7 +402,7 @@ mod tests {
         let mut stats = RollingStats::default();
         _ = stats.write(&[0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0, 4]);
         assert_eq!(stats.mean(), 2.0);
-        assert_eq!(stats.std_deviation(), 0.816496611);
+        assert_eq!(stats.std_deviation(), 0.816_496_6);
         LOG.info(stats.std_distribution().to_string().as_str());
     }
 
@@ -164,7 +427,7 @@ mod tests {
         // and therefore window_size is 3 we are taking 0,0,0,1 from previous write call.
         _ = stats.write(&[0, 0, 0, 1, 0, 0, 0, 2, 0]);
         _ = stats.write(&[0, 0, 1, 0, 0, 0, 2, 0, 0, 6]); //reminder 0,0,6
-        assert_eq!(stats.mean(), 1.33333337);
+        assert_eq!(stats.mean(), 1.333_333_4);
     }
 
     #[test]
@@ -200,6 +463,147 @@ mod tests {
         assert_eq!(stats.mean(), 2.0);
     }
 
+    #[test]
+    fn test_capability() {
+        let mut stats = RollingStats::default();
+        _ = stats.write(&[0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3]);
+        let capability = stats.capability(1.5, 4.0).unwrap();
+        assert_eq!(capability.fraction_out_of_spec, 1.0 / 3.0);
+        assert_eq!(capability.overall_sigma, 1.0);
+        // moving ranges follow arrival order
+        let capability = window_over_two_writes().capability(0.0, 10.0).unwrap();
+        assert!((capability.within_sigma - 8.0 / 1.128).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_fit() {
+        let mut stats = RollingStats::default();
+        _ = stats.write(&[0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3]);
+        let summary = stats.fit().unwrap();
+        assert_eq!(
+            summary.uniform.map(|f| f.distribution),
+            Some(FittedDistribution::Uniform { min: 1.0, max: 3.0 })
+        );
+        assert!(matches!(
+            summary.best().map(|f| f.distribution),
+            Some(FittedDistribution::Normal { mean, .. }) if mean == 2.0
+        ));
+        assert_ne!(stats.fitted_distribution(), 0.0);
+    }
+
+    #[test]
+    fn test_resampling() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut stats = RollingStats::default();
+        assert_eq!(stats.empirical_sample(&mut rng), None);
+        _ = stats.write(&[0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3]);
+        assert!((1..=3).contains(&stats.empirical_sample(&mut rng).unwrap()));
+        assert!(stats.smoothed_sample(&mut rng).is_some());
+        let (lower, upper) = stats
+            .bootstrap_ci::<_, _, 200>(|v: &mut [i32]| Statistic::Mean.compute(v), 0.95, &mut rng)
+            .unwrap();
+        assert!(lower >= 1.0 && upper <= 3.0 && lower < upper);
+    }
+
+    #[test]
+    fn test_reservoir_next_to_window() {
+        let mut stats = RollingStats::default();
+        let mut reservoir = Reservoir::<_, 8>::seeded(3, ReservoirAlgorithm::L);
+        // values past the window of 3 reach the reservoir too
+        stats.write_through(
+            &[0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0, 4, 0, 0, 0, 5],
+            &mut reservoir,
+        );
+        assert!(stats.values().iter().eq([1, 2, 3].iter()));
+        assert_eq!(reservoir.samples(), &[1, 2, 3, 4, 5]);
+        // little endian write, decoded the same way for both, 6 and -7
+        stats.write_through(&[6, 0, 0, 0, 249, 255, 255, 255], &mut reservoir);
+        assert_eq!(&stats.values()[..2], &[6, -7]);
+        assert_eq!(reservoir.samples(), &[1, 2, 3, 4, 5, 6, -7]);
+        assert_eq!(reservoir.seen(), 7);
+    }
+
+    #[test]
+    fn test_forecast() {
+        let mut stats = RollingStats::default();
+        _ = stats.write(&[0, 0, 0, 2, 0, 0, 0, 4, 0, 0, 0, 6]);
+        let forecast = stats.holt(0.5, 0.5).unwrap().forecast(2, 0.95).unwrap();
+        assert_eq!(forecast.value, 10.0);
+        // window of 3 values is too short for AR(1) with intercept
+        assert_eq!(stats.ar(1), None);
+
+        // halving series over two writes, fitted in arrival order
+        let mut stats = RollingStats::new(6);
+        _ = stats.write(&[0, 0, 0, 64, 0, 0, 0, 32, 0, 0, 0, 16, 0, 0, 0, 8]);
+        _ = stats.write(&[0, 0, 0, 4, 0, 0, 0, 2]);
+        let model = stats.ar(1).unwrap();
+        assert!((model.coefficient(0).unwrap() - 0.5).abs() < 1e-6);
+        assert!(model.intercept().abs() < 1e-6);
+        assert!((model.forecast(1, 0.95).unwrap().value - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_mean_ci() {
+        let mut stats = RollingStats::default();
+        assert_eq!(stats.mean_ci(0.95), None);
+        _ = stats.write(&[0, 0, 0, 1, 0, 0, 0, 2]);
+        assert!(!stats.is_warmed_up());
+        _ = stats.write(&[0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3]);
+        assert!(stats.is_warmed_up());
+        assert_eq!(stats.standard_error(), Some(0.577_350_26));
+        let (lower, upper) = stats.mean_ci(0.95).unwrap();
+        assert!((lower + 0.484_138).abs() < 1e-5);
+        assert!((upper - 4.484_138).abs() < 1e-5);
+
+        // negative window, mean and interval agree
+        _ = stats.write(&[255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255]);
+        assert_eq!(stats.mean(), -1.0);
+        assert_eq!(stats.mean_ci(0.95), Some((-1.0, -1.0)));
+    }
+
+    #[test]
+    fn test_mean_ci_large_sample() {
+        let mut stats = RollingStats::new(40);
+        let mut buf = [0u8; 160];
+        for (i, chunk) in buf.chunks_exact_mut(4).enumerate() {
+            chunk[3] = i as u8;
+        }
+        _ = stats.write(&buf);
+        assert_eq!(stats.mean(), 19.5);
+        // normal quantile 1.96 with standard error sqrt(40 * 41 / 12 / 40)
+        let (lower, upper) = stats.mean_ci(0.95).unwrap();
+        assert!((lower - 15.877_2).abs() < 1e-3);
+        assert!((upper - 23.122_8).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_compare_windows() {
+        let mut a = RollingStats::default();
+        let mut b = RollingStats::default();
+        _ = a.write(&[0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3]);
+        _ = b.write(&[0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3]);
+        let result = a.welch_t_test(&b.summary()).unwrap();
+        assert_eq!((result.statistic, result.p_value), (0.0, 1.0));
+        assert_eq!(a.mann_whitney(b.values()).unwrap().p_value, 1.0);
+    }
+
+    #[test]
+    fn test_reset_window_after_change() {
+        let mut stats = RollingStats::default();
+        let mut cusum = Cusum::new(1.0, 0.5, 2.0);
+        _ = stats.write(&[0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 1]);
+        assert_eq!(stats.detect_change(&mut cusum, true), None);
+        _ = stats.write(&[0, 0, 0, 1, 0, 0, 0, 3, 0, 0, 0, 3]);
+        let change = stats.detect_change(&mut cusum, true).unwrap();
+        assert_eq!((change.start, change.direction), (4, Direction::Up));
+        assert_eq!(stats.mean(), 3.0);
+        stats.reset();
+        assert_eq!(stats.mean(), 0.0);
+    }
+
     #[test]
     fn test_incomplete_i32() {
         let mut stats = RollingStats::default();
@@ -207,6 +611,32 @@ mod tests {
         _ = stats.write(&[0, 0, 1]);
         assert_eq!(stats.mean(), 0.0);
     }
+
+    #[test]
+    fn test_trend() {
+        let mut stats = RollingStats::default();
+        _ = stats.write(&[0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0, 4]);
+        assert_eq!(stats.trend().map(|t| t.slope), Some(1.0));
+        assert_eq!(window_over_two_writes().trend().map(|t| t.slope), Some(0.0));
+    }
+
+    // window spans two writes, kept value 1 is older than fresh 9 and 1
+    fn window_over_two_writes() -> RollingStats {
+        let mut stats = RollingStats::default();
+        _ = stats.write(&[0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 1]);
+        _ = stats.write(&[0, 0, 0, 9, 0, 0, 0, 1]);
+        stats
+    }
+
+    #[test]
+    fn test_chronological_values() {
+        let stats = window_over_two_writes();
+        assert!(stats.chronological_values().iter().eq([1, 9, 1].iter()));
+        let mut stats = RollingStats::default();
+        // bytes after the window are skipped
+        _ = stats.write(&[0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0, 4]);
+        assert!(stats.chronological_values().iter().eq([1, 2, 3].iter()));
+    }
 }
 
 #[cfg(test)]
@@ -218,9 +648,9 @@ mod tests_no_std {
     #[test]
     fn test_one_write() {
         let mut stats = RollingStats::default();
-        _ = stats.write_no_std(&[0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0, 4]);
+        stats.write_no_std(&[0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0, 4]);
         assert_eq!(stats.mean(), 2.0);
-        assert_eq!(stats.std_deviation(), 0.816496611);
+        assert_eq!(stats.std_deviation(), 0.816_496_6);
         assert_ne!(stats.std_distribution(), 0.0);
     }
 }
@@ -0,0 +1,270 @@
+use crate::byte_converter::LOG;
+use crate::feed::Push;
+use crate::logs::Logger;
+
+pub enum HistogramError {
+    // histograms have different significant digits, so their buckets don`t match
+    LayoutMismatch,
+    // merged histogram has counts above the range of this one
+    OutOfRange,
+}
+
+// biggest supported precision, 5 digits already needs about 2M counters (8 MB)
+pub const MAX_SIGNIFICANT_DIGITS: u32 = 5;
+
+// number of sub buckets in one power of two range, first power of two bigger than 2 * 10^digits
+const fn sub_bucket_count(digits: u32) -> u64 {
+    (2 * 10u64.pow(digits)).next_power_of_two()
+}
+
+// number of counters needed to cover 0..=i32::MAX with given significant digits.
+// Use it to size the histogram, e.g. LogHistogram<{ log_histogram_len(2) }>.
+pub const fn log_histogram_len(digits: u32) -> usize {
+    let sub_buckets = sub_bucket_count(digits);
+    let mut buckets = 1;
+    let mut smallest_untrackable = sub_buckets;
+    while smallest_untrackable <= i32::MAX as u64 {
+        smallest_untrackable <<= 1;
+        buckets += 1;
+    }
+    ((buckets + 1) * (sub_buckets / 2)) as usize
+}
+
+// Log-linear (HDR style) histogram for values spanning several orders of magnitude, e.g. latencies in us.
+// Every power of two range is split into the same number of linear sub buckets, so any recorded value is
+// represented with relative error below 10^-digits. Counts are cumulative since creation or the last reset,
+// histograms of several instances can be merged. Negative values are recorded as 0.
+pub struct LogHistogram<const B: usize> {
+    counts: [u32; B],
+    digits: u32,
+    // log2 of half of the sub buckets
+    sub_bucket_half_magnitude: u32,
+    sub_bucket_mask: u64,
+    total: u64,
+    min: i32,
+    max: i32,
+}
+
+impl<const B: usize> LogHistogram<B> {
+    pub fn new(significant_digits: u32) -> Self {
+        let mut digits = significant_digits.clamp(1, MAX_SIGNIFICANT_DIGITS);
+        if digits != significant_digits {
+            LOG.warn("significant digits has to be in 1..=5 range");
+        }
+        while digits > 1 && log_histogram_len(digits) > B {
+            LOG.warn("histogram is too small for requested digits, using less precision");
+            digits -= 1;
+        }
+        if log_histogram_len(digits) > B {
+            LOG.error("histogram is too small even for 1 digit, big values won`t be recorded");
+        }
+        let sub_buckets = sub_bucket_count(digits);
+        Self {
+            counts: [0; B],
+            digits,
+            sub_bucket_half_magnitude: sub_buckets.trailing_zeros() - 1,
+            sub_bucket_mask: sub_buckets - 1,
+            total: 0,
+            min: i32::MAX,
+            max: 0,
+        }
+    }
+
+    pub fn significant_digits(&self) -> u32 {
+        self.digits
+    }
+
+    // bucket is the power of two range, sub bucket the linear step inside it
+    fn bucket_index(&self, value: u64) -> u32 {
+        let leading_zero_count_base = 63 - self.sub_bucket_half_magnitude;
+        leading_zero_count_base - (value | self.sub_bucket_mask).leading_zeros()
+    }
+
+    fn counts_index(&self, value: u64) -> usize {
+        let bucket = self.bucket_index(value);
+        let sub_bucket = value >> bucket;
+        let bucket_base = ((bucket + 1) as u64) << self.sub_bucket_half_magnitude;
+        let sub_bucket_half = 1u64 << self.sub_bucket_half_magnitude;
+        (bucket_base + sub_bucket - sub_bucket_half) as usize
+    }
+
+    // lowest value and size of the value range represented by the counter
+    fn counter_range(&self, index: usize) -> (u64, u64) {
+        let sub_bucket_half = 1u64 << self.sub_bucket_half_magnitude;
+        let mut bucket = (index as u64 >> self.sub_bucket_half_magnitude) as i64 - 1;
+        let mut sub_bucket = (index as u64 & (sub_bucket_half - 1)) + sub_bucket_half;
+        // first half of the lowest bucket is stored before it
+        if bucket < 0 {
+            sub_bucket -= sub_bucket_half;
+            bucket = 0;
+        }
+        (sub_bucket << bucket, 1 << bucket)
+    }
+
+    pub fn record(&mut self, value: i32) {
+        self.record_n(value, 1);
+    }
+
+    // record the same value count times
+    pub fn record_n(&mut self, value: i32, count: u32) {
+        let value = value.max(0);
+        let index = self.counts_index(value as u64);
+        if let Some(counter) = self.counts.get_mut(index) {
+            *counter = counter.saturating_add(count);
+            self.total += count as u64;
+            self.min = self.min.min(value);
+            self.max = self.max.max(value);
+        } else {
+            LOG.error("value is out of the histogram range");
+        }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    pub fn min(&self) -> Option<i32> {
+        (self.total > 0).then_some(self.min)
+    }
+
+    pub fn max(&self) -> Option<i32> {
+        (self.total > 0).then_some(self.max)
+    }
+
+    // q is from 0 up to 100. Returns highest value equivalent to the percentile bucket, same as HdrHistogram.
+    pub fn percentile(&self, q: f32) -> Option<i32> {
+        if self.total == 0 {
+            return None;
+        }
+        if !(0.0..=100.0).contains(&q) {
+            LOG.warn("percentile has to be in 0..=100 range");
+            return None;
+        }
+        // at least one value has to be counted
+        let target = ((q as f64 / 100.0 * self.total as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (index, count) in self.counts.iter().enumerate() {
+            seen += *count as u64;
+            if seen >= target {
+                let (lowest, size) = self.counter_range(index);
+                let highest = (lowest + size - 1).min(i32::MAX as u64) as i32;
+                // exact max is better answer than the end of its bucket
+                return Some(highest.min(self.max));
+            }
+        }
+        Some(self.max)
+    }
+
+    // number of values in buckets above the bucket of threshold, values equivalent to threshold are not counted
+    pub fn count_above(&self, threshold: i32) -> u64 {
+        let first = self.counts_index(threshold.max(0) as u64) + 1;
+        self.counts
+            .get(first..)
+            .map(|counts| counts.iter().map(|c| *c as u64).sum())
+            .unwrap_or(0)
+    }
+
+    // add all counts of other histogram, both need the same significant digits
+    pub fn merge<const B2: usize>(
+        &mut self,
+        other: &LogHistogram<B2>,
+    ) -> Result<(), HistogramError> {
+        if self.digits != other.digits {
+            LOG.error("histograms with different significant digits can`t be merged");
+            return Err(HistogramError::LayoutMismatch);
+        }
+        // same digits means the same counter layout, only the array of other can be longer
+        if other.counts.iter().skip(B).any(|count| *count > 0) {
+            LOG.error("merged histogram has values out of this histogram range");
+            return Err(HistogramError::OutOfRange);
+        }
+        for (counter, count) in self.counts.iter_mut().zip(other.counts.iter()) {
+            *counter = counter.saturating_add(*count);
+        }
+        if other.total > 0 {
+            self.total += other.total;
+            self.min = self.min.min(other.min);
+            self.max = self.max.max(other.max);
+        }
+        Ok(())
+    }
+
+    pub fn reset(&mut self) {
+        self.counts.fill(0);
+        self.total = 0;
+        self.min = i32::MAX;
+        self.max = 0;
+    }
+}
+
+impl<const B: usize> Push for LogHistogram<B> {
+    fn push(&mut self, value: i32) {
+        self.record(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEN: usize = log_histogram_len(2);
+
+    #[test]
+    fn test_relative_error_across_magnitudes() {
+        assert_eq!(LEN, 3200);
+        for value in [1, 99, 1_000, 123_456, 50_000_000, i32::MAX] {
+            let mut hist = LogHistogram::<LEN>::new(2);
+            hist.record(value);
+            hist.record(0);
+            let p100 = hist.percentile(100.0).unwrap();
+            assert_eq!(p100, value);
+            let index = hist.counts_index(value as u64);
+            let (lowest, size) = hist.counter_range(index);
+            assert!(lowest <= value as u64 && (value as u64) < lowest + size);
+            assert!(size as f64 <= value as f64 / 100.0 || size == 1);
+        }
+    }
+
+    #[test]
+    fn test_tail_percentiles_and_threshold() {
+        let mut hist = LogHistogram::<LEN>::new(2);
+        hist.record_n(100, 990);
+        hist.record_n(10_000, 9);
+        hist.record(1_000_000);
+        assert_eq!(hist.percentile(50.0), Some(100));
+        assert_eq!(hist.percentile(99.0), Some(100));
+        assert_eq!(hist.percentile(99.5), Some(10_047));
+        assert_eq!(hist.percentile(100.0), Some(1_000_000));
+        assert_eq!(hist.count_above(1_000), 10);
+        assert_eq!(hist.count_above(10_000), 1);
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut a = LogHistogram::<LEN>::new(2);
+        let mut b = LogHistogram::<LEN>::new(2);
+        a.record(5);
+        b.record(500);
+        b.record(7);
+        assert!(a.merge(&b).is_ok());
+        assert_eq!(a.total(), 3);
+        assert_eq!(a.max(), Some(500));
+        let c = LogHistogram::<{ log_histogram_len(1) }>::new(1);
+        assert!(a.merge(&c).is_err());
+    }
+
+    #[test]
+    fn test_merge_into_smaller_array() {
+        let mut small = LogHistogram::<64>::new(1);
+        let mut big = LogHistogram::<{ log_histogram_len(1) }>::new(1);
+        big.record(3);
+        assert!(small.merge(&big).is_ok());
+        assert_eq!(small.total(), 1);
+        // counts which don`t fit are not dropped silently, nothing is merged
+        big.record(1_000_000);
+        assert!(matches!(small.merge(&big), Err(HistogramError::OutOfRange)));
+        assert_eq!(small.total(), 1);
+        assert!(big.merge(&small).is_ok());
+        assert_eq!(big.total(), 3);
+    }
+}
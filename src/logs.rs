@@ -32,17 +32,17 @@ impl Logger for StdLogger {
 
 #[cfg(not(feature = "std"))]
 impl Logger for NoStdLogger {
-    fn error(&self, msg: &str) {
+    fn error(&self, _msg: &str) {
         // write into file or to the serial so we can see debug msg
         //todo!();
     }
 
-    fn warn(&self, msg: &str) {
+    fn warn(&self, _msg: &str) {
         // write into file or to the serial so we can see debug msg
         //todo!();
     }
 
-    fn info(&self, msg: &str) {
+    fn info(&self, _msg: &str) {
         // write into file or to the serial so we can see debug msg
         //todo!();
     }
@@ -0,0 +1,162 @@
+use crate::feed::{Evict, Push};
+use crate::ring_buffer::RingBuffer;
+
+// Rolling minimum and maximum over the last window_size values.
+// Each extreme is kept in a monotonic deque of (sample index, value). Every value enters and leaves a deque
+// at most once, so push is O(1) amortized and queries are O(1). Deques never hold more than window_size
+// entries, so RingBuffer with the same window can`t overflow and nothing is allocated.
+pub struct RollingMinMax<const N: usize> {
+    // increasing values, front is the minimum of the window
+    min_deque: RingBuffer<(u64, i32), N>,
+    // decreasing values, front is the maximum of the window
+    max_deque: RingBuffer<(u64, i32), N>,
+    window_size: usize,
+    // number of values pushed since start, index of the next value
+    count: u64,
+    // index of the oldest value in the window
+    start: u64,
+}
+
+impl<const N: usize> RollingMinMax<N> {
+    pub fn new(window_size: usize) -> Self {
+        let min_deque = RingBuffer::new(window_size);
+        Self {
+            // window_size is validated by RingBuffer
+            window_size: min_deque.window_size(),
+            min_deque,
+            max_deque: RingBuffer::new(window_size),
+            count: 0,
+            start: 0,
+        }
+    }
+
+    pub fn push_value(&mut self, value: i32) {
+        let index = self.count;
+        self.count += 1;
+
+        // drop extremes which are older than window_size
+        let window_size = self.window_size as u64;
+        self.start = self.start.max((index + 1).saturating_sub(window_size));
+        self.drop_evicted();
+
+        // values which can`t be extreme anymore, because newer value is better, are dropped.
+        // For equal values the newest one is kept.
+        while matches!(self.min_deque.newest(), Some((_, v)) if *v >= value) {
+            self.min_deque.pop_newest();
+        }
+        while matches!(self.max_deque.newest(), Some((_, v)) if *v <= value) {
+            self.max_deque.pop_newest();
+        }
+        self.min_deque.push((index, value));
+        self.max_deque.push((index, value));
+    }
+
+    // extremes with index before start are not in the window anymore
+    fn drop_evicted(&mut self) {
+        while matches!(self.min_deque.oldest(), Some((i, _)) if *i < self.start) {
+            self.min_deque.pop_oldest();
+        }
+        while matches!(self.max_deque.oldest(), Some((i, _)) if *i < self.start) {
+            self.max_deque.pop_oldest();
+        }
+    }
+
+    pub fn min(&self) -> Option<i32> {
+        self.min_deque.oldest().map(|(_, v)| *v)
+    }
+
+    pub fn max(&self) -> Option<i32> {
+        self.max_deque.oldest().map(|(_, v)| *v)
+    }
+
+    // index of the sample with minimum value, counted from the first pushed value
+    pub fn argmin(&self) -> Option<u64> {
+        self.min_deque.oldest().map(|(i, _)| *i)
+    }
+
+    // index of the sample with maximum value, counted from the first pushed value
+    pub fn argmax(&self) -> Option<u64> {
+        self.max_deque.oldest().map(|(i, _)| *i)
+    }
+
+    // how many values were pushed after the minimum, 0 means the newest value is the minimum
+    pub fn min_age(&self) -> Option<u64> {
+        self.argmin().map(|i| self.count - 1 - i)
+    }
+
+    // how many values were pushed after the maximum, 0 means the newest value is the maximum
+    pub fn max_age(&self) -> Option<u64> {
+        self.argmax().map(|i| self.count - 1 - i)
+    }
+
+    // max - min, i64 because the difference of two i32 can overflow i32
+    pub fn range(&self) -> Option<i64> {
+        Some(self.max()? as i64 - self.min()? as i64)
+    }
+
+    // number of values in the window
+    pub fn len(&self) -> usize {
+        (self.count - self.start) as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == self.start
+    }
+}
+
+impl<const N: usize> Push for RollingMinMax<N> {
+    fn push(&mut self, value: i32) {
+        self.push_value(value);
+    }
+}
+
+impl<const N: usize> Evict for RollingMinMax<N> {
+    fn evict_oldest(&mut self) -> bool {
+        if self.is_empty() {
+            return false;
+        }
+        self.start += 1;
+        self.drop_evicted();
+        true
+    }
+
+    fn window_len(&self) -> usize {
+        self.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_min_max_evicted_with_window() {
+        let mut stats = RollingMinMax::<8>::new(3);
+        assert_eq!(stats.min(), None);
+        for value in [5, 1, 4, 7, 2] {
+            stats.push_value(value);
+        }
+        // window is 4, 7, 2
+        assert_eq!(stats.min(), Some(2));
+        assert_eq!(stats.max(), Some(7));
+        assert_eq!(stats.argmax(), Some(3));
+        assert_eq!(stats.max_age(), Some(1));
+        assert_eq!(stats.min_age(), Some(0));
+        assert_eq!(stats.range(), Some(5));
+        stats.push_value(3);
+        stats.push_value(3);
+        // window is 2, 3, 3 and the 7 is gone
+        assert_eq!(stats.max(), Some(3));
+        assert_eq!(stats.argmax(), Some(6));
+        assert_eq!(stats.min(), Some(2));
+    }
+
+    #[test]
+    fn test_range_does_not_overflow() {
+        let mut stats = RollingMinMax::<2>::new(2);
+        stats.push_value(i32::MIN);
+        stats.push_value(i32::MAX);
+        assert_eq!(stats.range(), Some(u32::MAX as i64));
+        assert_eq!(stats.len(), 2);
+    }
+}
@@ -0,0 +1,274 @@
+use crate::feed::{Evict, Push};
+use crate::ring_buffer::RingBuffer;
+
+// sums are re-centred when (mean - shift)^2 exceeds this multiple of the variance, error of the fourth central
+// moment grows with the square of the ratio
+const RECENTRE_RATIO: f64 = 1e4;
+
+// Rolling mean, variance, skewness and kurtosis over the last window_size values.
+// Power sums of (value - shift) up to the fourth power are updated on every push/evict. Shift is the first value
+// and later the rounded window mean from the last recompute, which keeps the sums small. Sums are recomputed when
+// the mean moves far from shift compared with the spread (e.g. after a level shift, where the fourth powers would
+// lose all precision) and every time the window is completely replaced, because float sums slowly drift when
+// values are added and removed. Both are amortized O(1) for streams without repeated level shifts.
+pub struct RollingMoments<const N: usize> {
+    window: RingBuffer<i32, N>,
+    shift: i32,
+    // sums[k] = sum((value - shift)^(k + 1))
+    sums: [f64; 4],
+    // evictions since the last recompute of sums
+    evicted: usize,
+}
+
+impl<const N: usize> RollingMoments<N> {
+    pub fn new(window_size: usize) -> Self {
+        Self {
+            window: RingBuffer::new(window_size),
+            shift: 0,
+            sums: [0.0; 4],
+            evicted: 0,
+        }
+    }
+
+    pub fn push_value(&mut self, value: i32) {
+        if self.window.is_empty() {
+            self.shift = value;
+        }
+        if let Some(evicted) = self.window.push(value) {
+            self.add(evicted, -1.0);
+            self.evicted += 1;
+        }
+        self.add(value, 1.0);
+        if self.evicted >= self.window.window_size() || self.is_off_centre() {
+            self.recompute();
+        }
+    }
+
+    fn is_off_centre(&self) -> bool {
+        let n = self.window.len() as f64;
+        let a = self.sums[0] / n;
+        let m2 = self.sums[1] / n - a * a;
+        a * a > RECENTRE_RATIO * m2.max(0.0)
+    }
+
+    fn add(&mut self, value: i32, sign: f64) {
+        let d = value as f64 - self.shift as f64;
+        let mut power = sign;
+        for sum in self.sums.iter_mut() {
+            power *= d;
+            *sum += power;
+        }
+    }
+
+    fn recompute(&mut self) {
+        self.evicted = 0;
+        self.sums = [0.0; 4];
+        let n = self.window.len();
+        if n > 0 {
+            let sum: i64 = (0..n)
+                .filter_map(|i| self.window.get(i).map(|v| *v as i64))
+                .sum();
+            self.shift = (sum as f64 / n as f64).round() as i32;
+        }
+        for i in 0..n {
+            if let Some(value) = self.window.get(i).copied() {
+                self.add(value, 1.0);
+            }
+        }
+    }
+
+    // drop all values, e.g. after a change in the stream
+    pub fn clear(&mut self) {
+        self.window.clear();
+        self.sums = [0.0; 4];
+        self.evicted = 0;
+    }
+
+    pub fn len(&self) -> usize {
+        self.window.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.window.is_empty()
+    }
+
+    pub fn window(&self) -> &RingBuffer<i32, N> {
+        &self.window
+    }
+
+    // mean of shifted values and central moments m2, m3, m4 (population, divided by n)
+    fn central_moments(&self) -> Option<(f64, f64, f64, f64)> {
+        if self.window.is_empty() {
+            return None;
+        }
+        let n = self.window.len() as f64;
+        let [s1, s2, s3, s4] = self.sums.map(|s| s / n);
+        let a = s1;
+        let m2 = s2 - a * a;
+        let m3 = s3 - 3.0 * a * s2 + 2.0 * a * a * a;
+        let m4 = s4 - 4.0 * a * s3 + 6.0 * a * a * s2 - 3.0 * a * a * a * a;
+        // integer values which are not all equal have m2 >= (n - 1) / n^2, anything smaller is rounding
+        let m2 = if m2 < 0.5 / (n * n) { 0.0 } else { m2 };
+        Some((a, m2, m3, m4))
+    }
+
+    pub fn mean(&self) -> f32 {
+        match self.central_moments() {
+            Some((a, ..)) => (self.shift as f64 + a) as f32,
+            None => 0.0,
+        }
+    }
+
+    // population variance
+    pub fn variance(&self) -> f32 {
+        self.central_moments()
+            .map(|(_, m2, ..)| m2 as f32)
+            .unwrap_or(0.0)
+    }
+
+    // unbiased variance (divided by n - 1), 0 for less than 2 values
+    pub fn sample_variance(&self) -> f32 {
+        let n = self.window.len() as f32;
+        if n < 2.0 {
+            return 0.0;
+        }
+        self.variance() * n / (n - 1.0)
+    }
+
+    // population standard deviation, same as RollingStats::std_deviation
+    pub fn std_deviation(&self) -> f32 {
+        self.variance().sqrt()
+    }
+
+    // third standardized moment g1.
+    // None for empty window and for constant window where the spread is 0 and skewness is undefined.
+    pub fn skewness(&self) -> Option<f32> {
+        let (_, m2, m3, _) = self.central_moments()?;
+        if m2 == 0.0 {
+            return None;
+        }
+        Some((m3 / (m2 * m2 * m2).sqrt()) as f32)
+    }
+
+    // adjusted Fisher-Pearson skewness G1, needs at least 3 values
+    pub fn sample_skewness(&self) -> Option<f32> {
+        let n = self.window.len() as f64;
+        if n < 3.0 {
+            return None;
+        }
+        let g1 = self.skewness()? as f64;
+        Some((g1 * (n * (n - 1.0)).sqrt() / (n - 2.0)) as f32)
+    }
+
+    // fourth standardized moment minus 3, so normal distribution has 0.
+    // None for empty window and for constant window.
+    pub fn excess_kurtosis(&self) -> Option<f32> {
+        let (_, m2, _, m4) = self.central_moments()?;
+        if m2 == 0.0 {
+            return None;
+        }
+        Some((m4 / (m2 * m2) - 3.0) as f32)
+    }
+
+    // bias corrected excess kurtosis G2, needs at least 4 values
+    pub fn sample_excess_kurtosis(&self) -> Option<f32> {
+        let n = self.window.len() as f64;
+        if n < 4.0 {
+            return None;
+        }
+        let g2 = self.excess_kurtosis()? as f64;
+        Some((((n + 1.0) * g2 + 6.0) * (n - 1.0) / ((n - 2.0) * (n - 3.0))) as f32)
+    }
+}
+
+impl<const N: usize> Push for RollingMoments<N> {
+    fn push(&mut self, value: i32) {
+        self.push_value(value);
+    }
+}
+
+impl<const N: usize> Evict for RollingMoments<N> {
+    fn evict_oldest(&mut self) -> bool {
+        let Some(evicted) = self.window.pop_oldest() else {
+            return false;
+        };
+        if self.window.is_empty() {
+            self.clear();
+            return true;
+        }
+        self.add(evicted, -1.0);
+        self.evicted += 1;
+        if self.evicted >= self.window.window_size() || self.is_off_centre() {
+            self.recompute();
+        }
+        true
+    }
+
+    fn window_len(&self) -> usize {
+        self.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_moments_follow_window() {
+        let mut stats = RollingMoments::<8>::new(4);
+        for value in [1000, 1, 2, 3, 10] {
+            stats.push_value(value);
+        }
+        // window is 1, 2, 3, 10
+        assert_eq!(stats.mean(), 4.0);
+        assert_eq!(stats.variance(), 12.5);
+        let skewness = stats.skewness().unwrap();
+        assert!((skewness - 1.01823).abs() < 1e-4);
+        let kurtosis = stats.excess_kurtosis().unwrap();
+        assert!((kurtosis + 0.76960).abs() < 1e-4);
+        assert!((stats.sample_skewness().unwrap() - 1.76363).abs() < 1e-4);
+        assert!((stats.sample_excess_kurtosis().unwrap() - 3.22800).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_tiny_and_constant_windows() {
+        let mut stats = RollingMoments::<8>::new(8);
+        assert_eq!(stats.skewness(), None);
+        stats.push_value(7);
+        stats.push_value(7);
+        assert_eq!(stats.variance(), 0.0);
+        assert_eq!(stats.skewness(), None);
+        assert_eq!(stats.excess_kurtosis(), None);
+        stats.push_value(8);
+        assert_eq!(stats.sample_excess_kurtosis(), None);
+        assert!(stats.sample_skewness().is_some());
+    }
+
+    #[test]
+    fn test_no_drift_after_many_evictions() {
+        let mut stats = RollingMoments::<16>::new(16);
+        for i in 0..10_000 {
+            stats.push_value(1_000_000 + (i * 37) % 101);
+        }
+        stats.push_value(5);
+        for _ in 0..15 {
+            stats.push_value(5);
+        }
+        assert_eq!(stats.mean(), 5.0);
+        assert_eq!(stats.variance(), 0.0);
+    }
+
+    #[test]
+    fn test_level_shift_keeps_precision() {
+        let mut stats = RollingMoments::<16>::new(16);
+        stats.push_value(0);
+        // 0 is evicted by the last value, long before the window is completely replaced again
+        for i in 0..16 {
+            stats.push_value(if i < 9 { 1_000_000 } else { 1_000_001 });
+        }
+        assert_eq!(stats.mean(), 1_000_000.44);
+        assert!((stats.variance() - 0.246_093_75).abs() < 1e-6);
+        assert!((stats.skewness().unwrap() - 0.251_976).abs() < 1e-4);
+        assert!((stats.excess_kurtosis().unwrap() + 1.936_508).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,186 @@
+use crate::byte_converter::LOG;
+use crate::feed::{Evict, Push};
+use crate::logs::Logger;
+use crate::ring_buffer::RingBuffer;
+
+// Summary of the stream which can answer any quantile. RollingQuantiles is the exact backend over the window,
+// TDigest is the approximate backend with constant memory for unbounded streams.
+pub trait QuantileSummary: Push {
+    // q is from 0 up to 1
+    fn quantile(&mut self, q: f32) -> Option<f32>;
+    // number of values the summary is built from
+    fn count(&self) -> u64;
+}
+
+// Exact median and percentiles over the last window_size values.
+// Next to the window in arrival order we keep the same values sorted in a fixed array. New value is placed
+// by binary search and evicted value is found by binary search too, so push costs O(log N) comparisons
+// plus one memmove of at most N values. Every order statistic is then a direct index into the sorted array.
+pub struct RollingQuantiles<const N: usize> {
+    window: RingBuffer<i32, N>,
+    sorted: [i32; N],
+}
+
+impl<const N: usize> RollingQuantiles<N> {
+    pub fn new(window_size: usize) -> Self {
+        Self {
+            window: RingBuffer::new(window_size),
+            sorted: [0; N],
+        }
+    }
+
+    pub fn push_value(&mut self, value: i32) {
+        let evicted = self.window.push(value);
+        // window already counts the new value, but sorted doesn`t have it yet
+        let Some(len) = self.window.len().checked_sub(1) else {
+            return;
+        };
+        if let Some(evicted) = evicted {
+            // evicted value is still in sorted, so there are len + 1 sorted values
+            self.remove_sorted(evicted, len + 1);
+        }
+        self.insert_sorted(value, len);
+    }
+
+    // remove value from sorted[..len]
+    fn remove_sorted(&mut self, value: i32, len: usize) {
+        if let Ok(position) = self.sorted[..len].binary_search(&value) {
+            self.sorted.copy_within(position + 1..len, position);
+        }
+    }
+
+    // place value into sorted[..=len], first len values are already sorted
+    fn insert_sorted(&mut self, value: i32, len: usize) {
+        let position = self.sorted[..len].partition_point(|v| *v < value);
+        self.sorted.copy_within(position..len, position + 1);
+        self.sorted[position] = value;
+    }
+
+    // window values in ascending order
+    pub fn sorted(&self) -> &[i32] {
+        &self.sorted[..self.window.len()]
+    }
+
+    pub fn len(&self) -> usize {
+        self.window.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.window.is_empty()
+    }
+
+    pub fn median(&self) -> Option<f32> {
+        self.percentile(50.0)
+    }
+
+    // q is from 0 up to 100. Values between two ranks are linearly interpolated.
+    pub fn percentile(&self, q: f32) -> Option<f32> {
+        if !(0.0..=100.0).contains(&q) {
+            LOG.warn("percentile has to be in 0..=100 range");
+            return None;
+        }
+        quantile_of_sorted(self.sorted(), q / 100.0)
+    }
+
+    // at which percentile would value x sit in the current window, equal values count as half
+    pub fn rank(&self, x: i32) -> Option<f32> {
+        let sorted = self.sorted();
+        if sorted.is_empty() {
+            return None;
+        }
+        let below = sorted.partition_point(|v| *v < x);
+        let equal = sorted[below..].partition_point(|v| *v <= x);
+        Some((below as f32 + equal as f32 * 0.5) / sorted.len() as f32 * 100.0)
+    }
+}
+
+// q from 0 up to 1, linear interpolation between closest ranks
+pub(crate) fn quantile_of_sorted<T: Copy + Into<f64>>(sorted: &[T], q: f32) -> Option<f32> {
+    let last = sorted.len().checked_sub(1)?;
+    let position = q as f64 * last as f64;
+    let lower = position.floor() as usize;
+    let upper = position.ceil() as usize;
+    // position is in 0..=last, so both indexes are in bounds
+    let fraction = position - lower as f64;
+    let low: f64 = sorted[lower].into();
+    let high: f64 = sorted[upper].into();
+    Some((low + (high - low) * fraction) as f32)
+}
+
+impl<const N: usize> Push for RollingQuantiles<N> {
+    fn push(&mut self, value: i32) {
+        self.push_value(value);
+    }
+}
+
+impl<const N: usize> Evict for RollingQuantiles<N> {
+    fn evict_oldest(&mut self) -> bool {
+        match self.window.pop_oldest() {
+            Some(evicted) => {
+                // window doesn`t count evicted value anymore, sorted still has it
+                self.remove_sorted(evicted, self.window.len() + 1);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn window_len(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<const N: usize> QuantileSummary for RollingQuantiles<N> {
+    fn quantile(&mut self, q: f32) -> Option<f32> {
+        self.percentile(q * 100.0)
+    }
+
+    fn count(&self) -> u64 {
+        self.len() as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_median_follows_window() {
+        let mut stats = RollingQuantiles::<8>::new(4);
+        assert_eq!(stats.median(), None);
+        for value in [9, 1, 5, 3] {
+            stats.push_value(value);
+        }
+        assert_eq!(stats.sorted(), &[1, 3, 5, 9]);
+        assert_eq!(stats.median(), Some(4.0));
+        stats.push_value(2); // 9 is evicted
+        assert_eq!(stats.sorted(), &[1, 2, 3, 5]);
+        assert_eq!(stats.median(), Some(2.5));
+        assert_eq!(stats.percentile(100.0), Some(5.0));
+        assert_eq!(stats.percentile(101.0), None);
+    }
+
+    #[test]
+    fn test_percentile_with_duplicates() {
+        let mut stats = RollingQuantiles::<16>::new(11);
+        for value in [0, 10, 20, 30, 40, 50, 60, 70, 80, 90, 100] {
+            stats.push_value(value);
+        }
+        assert_eq!(stats.percentile(90.0), Some(90.0));
+        assert_eq!(stats.percentile(95.0), Some(95.0));
+        stats.push_value(100);
+        stats.push_value(100);
+        assert_eq!(stats.sorted()[7..], [90, 100, 100, 100]);
+    }
+
+    #[test]
+    fn test_rank() {
+        let mut stats = RollingQuantiles::<4>::new(4);
+        for value in [1, 2, 2, 3] {
+            stats.push_value(value);
+        }
+        assert_eq!(stats.rank(2), Some(50.0));
+        assert_eq!(stats.rank(0), Some(0.0));
+        assert_eq!(stats.rank(3), Some(87.5));
+    }
+}
@@ -0,0 +1,169 @@
+use crate::feed::Push;
+use crate::ring_buffer::RingBuffer;
+
+// Rolling covariance, Pearson correlation and least squares line y = slope * x + intercept over the last
+// window_size (x, y) pairs, e.g. temperature vs current. Sums are exact integers, so push/evict is O(1)
+// and can`t drift. From bytes the values are taken interleaved as x, y, x, y...
+pub struct PairedRollingStats<const N: usize> {
+    window: RingBuffer<(i32, i32), N>,
+    // x waiting for its y
+    partial: Option<i32>,
+    sum_x: i64,
+    sum_y: i64,
+    sum_xx: i128,
+    sum_yy: i128,
+    sum_xy: i128,
+}
+
+impl<const N: usize> PairedRollingStats<N> {
+    pub fn new(window_size: usize) -> Self {
+        Self {
+            window: RingBuffer::new(window_size),
+            partial: None,
+            sum_x: 0,
+            sum_y: 0,
+            sum_xx: 0,
+            sum_yy: 0,
+            sum_xy: 0,
+        }
+    }
+
+    pub fn push_pair(&mut self, x: i32, y: i32) {
+        if let Some((old_x, old_y)) = self.window.push((x, y)) {
+            self.update_sums(old_x, old_y, -1);
+        }
+        self.update_sums(x, y, 1);
+    }
+
+    fn update_sums(&mut self, x: i32, y: i32, sign: i64) {
+        let (x, y) = (x as i64, y as i64);
+        self.sum_x += sign * x;
+        self.sum_y += sign * y;
+        self.sum_xx += (sign * x * x) as i128;
+        self.sum_yy += (sign * y * y) as i128;
+        self.sum_xy += (sign * x * y) as i128;
+    }
+
+    pub fn len(&self) -> usize {
+        self.window.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.window.is_empty()
+    }
+
+    pub fn mean_x(&self) -> f32 {
+        self.mean(self.sum_x)
+    }
+
+    pub fn mean_y(&self) -> f32 {
+        self.mean(self.sum_y)
+    }
+
+    fn mean(&self, sum: i64) -> f32 {
+        if self.window.is_empty() {
+            return 0.0;
+        }
+        (sum as f64 / self.window.len() as f64) as f32
+    }
+
+    // n^2 * covariance (or variance) computed exactly, n * sum(ab) - sum(a) * sum(b)
+    fn co_moment(&self, sum_ab: i128, sum_a: i64, sum_b: i64) -> i128 {
+        self.window.len() as i128 * sum_ab - sum_a as i128 * sum_b as i128
+    }
+
+    // population covariance of x and y
+    pub fn covariance(&self) -> f32 {
+        if self.window.is_empty() {
+            return 0.0;
+        }
+        let n = self.window.len() as f64;
+        (self.co_moment(self.sum_xy, self.sum_x, self.sum_y) as f64 / (n * n)) as f32
+    }
+
+    // Pearson correlation, None when x or y is constant in the window
+    pub fn correlation(&self) -> Option<f32> {
+        let sxx = self.co_moment(self.sum_xx, self.sum_x, self.sum_x);
+        let syy = self.co_moment(self.sum_yy, self.sum_y, self.sum_y);
+        if sxx == 0 || syy == 0 {
+            return None;
+        }
+        let sxy = self.co_moment(self.sum_xy, self.sum_x, self.sum_y);
+        Some((sxy as f64 / (sxx as f64 * syy as f64).sqrt()) as f32)
+    }
+
+    // least squares slope of y over x, None when x is constant in the window
+    pub fn slope(&self) -> Option<f32> {
+        let sxx = self.co_moment(self.sum_xx, self.sum_x, self.sum_x);
+        if sxx == 0 {
+            return None;
+        }
+        let sxy = self.co_moment(self.sum_xy, self.sum_x, self.sum_y);
+        Some((sxy as f64 / sxx as f64) as f32)
+    }
+
+    pub fn intercept(&self) -> Option<f32> {
+        let slope = self.slope()? as f64;
+        Some((self.mean_y() as f64 - slope * self.mean_x() as f64) as f32)
+    }
+
+    // coefficient of determination of the least squares line
+    pub fn r_squared(&self) -> Option<f32> {
+        let r = self.correlation()?;
+        Some(r * r)
+    }
+}
+
+// values from the byte stream are taken as x, y, x, y...
+impl<const N: usize> Push for PairedRollingStats<N> {
+    fn push(&mut self, value: i32) {
+        match self.partial.take() {
+            Some(x) => self.push_pair(x, value),
+            None => self.partial = Some(value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feed::ByteFeed;
+
+    #[test]
+    fn test_perfect_line() {
+        let mut stats = PairedRollingStats::<8>::new(3);
+        stats.push_pair(100, -100);
+        for x in 1..=3 {
+            stats.push_pair(x, 2 * x + 1);
+        }
+        assert_eq!(stats.slope(), Some(2.0));
+        assert_eq!(stats.intercept(), Some(1.0));
+        assert_eq!(stats.correlation(), Some(1.0));
+        assert_eq!(stats.r_squared(), Some(1.0));
+        assert_eq!(stats.covariance(), 4.0 / 3.0);
+    }
+
+    #[test]
+    fn test_interleaved_bytes() {
+        let mut feed = ByteFeed::new(PairedRollingStats::<8>::new(8));
+        // pairs (1, 3), (2, 1), (3, 2)
+        feed.write_no_std(&[
+            0, 0, 0, 1, 0, 0, 0, 3, 0, 0, 0, 2, 0, 0, 0, 1, 0, 0, 0, 3, 0, 0, 0, 2,
+        ]);
+        let stats = feed.inner();
+        assert_eq!(stats.len(), 3);
+        assert_eq!(stats.correlation(), Some(-0.5));
+        assert_eq!(stats.slope(), Some(-0.5));
+        assert_eq!(stats.r_squared(), Some(0.25));
+    }
+
+    #[test]
+    fn test_constant_x() {
+        let mut stats = PairedRollingStats::<4>::new(4);
+        stats.push_pair(5, 1);
+        stats.push_pair(5, 2);
+        assert_eq!(stats.slope(), None);
+        assert_eq!(stats.correlation(), None);
+        assert_eq!(stats.covariance(), 0.0);
+    }
+}
@@ -0,0 +1,174 @@
+use rand::Rng;
+use rand_distr::{Distribution, Normal};
+
+use crate::byte_converter::LOG;
+use crate::logs::Logger;
+use crate::order_stats::quantile_of_sorted;
+
+// Statistics which can be bootstrapped. Values are a resample, so they can be reordered freely.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Statistic {
+    Mean,
+    Median,
+    // population standard deviation, same as RollingStats::std_deviation
+    StdDeviation,
+}
+
+impl Statistic {
+    pub fn compute(&self, values: &mut [i32]) -> f32 {
+        if values.is_empty() {
+            return 0.0;
+        }
+        let n = values.len() as f64;
+        let mean = values.iter().map(|v| *v as f64).sum::<f64>() / n;
+        match self {
+            Statistic::Mean => mean as f32,
+            Statistic::Median => {
+                values.sort_unstable();
+                quantile_of_sorted(values, 0.5).unwrap_or(0.0)
+            }
+            Statistic::StdDeviation => {
+                let squares: f64 = values.iter().map(|v| (*v as f64 - mean).powi(2)).sum();
+                (squares / n).sqrt() as f32
+            }
+        }
+    }
+}
+
+// one of the values picked uniformly, None for no values
+pub fn empirical_sample<R: Rng + ?Sized>(values: &[i32], rng: &mut R) -> Option<i32> {
+    if values.is_empty() {
+        return None;
+    }
+    values.get(rng.gen_range(0..values.len())).copied()
+}
+
+// Silverman`s rule of thumb 1.06 * std_deviation * n^(-1/5), right for roughly normal values
+pub fn silverman_bandwidth(values: &[i32]) -> f32 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+    let n = values.len() as f64;
+    let mean = values.iter().map(|v| *v as f64).sum::<f64>() / n;
+    let variance = values
+        .iter()
+        .map(|v| (*v as f64 - mean).powi(2))
+        .sum::<f64>()
+        / n;
+    (1.06 * variance.sqrt() * n.powf(-0.2)) as f32
+}
+
+// Picked value plus gaussian noise with standard deviation bandwidth, i.e. a sample from kernel density estimate
+// of the values. Draws are continuous instead of repeating the stored values. None for no values.
+pub fn smoothed_sample<R: Rng + ?Sized>(
+    values: &[i32],
+    bandwidth: f32,
+    rng: &mut R,
+) -> Option<f32> {
+    let value = empirical_sample(values, rng)? as f32;
+    if bandwidth <= 0.0 {
+        return Some(value);
+    }
+    match Normal::new(value, bandwidth) {
+        Ok(kernel) => Some(kernel.sample(rng)),
+        Err(_) => {
+            LOG.error("bandwidth is not finite, value is not smoothed");
+            Some(value)
+        }
+    }
+}
+
+// Percentile bootstrap confidence interval (lower, upper) of the statistic for confidence in (0, 1).
+// B resamples of the values with replacement are drawn, N is the maximum number of values which can be resampled
+// (resample buffer lives on the stack). None for no values, more than N values or wrong confidence.
+pub fn bootstrap_ci<R, S, const N: usize, const B: usize>(
+    values: &[i32],
+    mut statistic: S,
+    confidence: f32,
+    rng: &mut R,
+) -> Option<(f32, f32)>
+where
+    R: Rng + ?Sized,
+    S: FnMut(&mut [i32]) -> f32,
+{
+    if !(confidence > 0.0 && confidence < 1.0) {
+        LOG.warn("confidence has to be in (0, 1) range");
+        return None;
+    }
+    if values.is_empty() || B == 0 {
+        return None;
+    }
+    if values.len() > N {
+        LOG.warn("more values than resample buffer N can hold");
+        return None;
+    }
+    let mut resample = [0i32; N];
+    let mut estimates = [0f32; B];
+    for estimate in estimates.iter_mut() {
+        let buffer = &mut resample[..values.len()];
+        for slot in buffer.iter_mut() {
+            *slot = values[rng.gen_range(0..values.len())];
+        }
+        *estimate = statistic(buffer);
+    }
+    estimates.sort_unstable_by(|a, b| a.total_cmp(b));
+    let alpha = (1.0 - confidence) / 2.0;
+    Some((
+        quantile_of_sorted(&estimates, alpha)?,
+        quantile_of_sorted(&estimates, 1.0 - alpha)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_statistics() {
+        assert_eq!(Statistic::Mean.compute(&mut [1, 2, 6]), 3.0);
+        assert_eq!(Statistic::Median.compute(&mut [6, 1, 2, 3]), 2.5);
+        assert_eq!(Statistic::StdDeviation.compute(&mut [1, 3]), 1.0);
+    }
+
+    #[test]
+    fn test_empirical_samples() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let values = [4, 8, 15];
+        for _ in 0..20 {
+            assert!(values.contains(&empirical_sample(&values, &mut rng).unwrap()));
+        }
+        assert_eq!(empirical_sample(&[], &mut rng), None);
+        assert_eq!(smoothed_sample(&[5], 0.0, &mut rng), Some(5.0));
+        let bandwidth = silverman_bandwidth(&values);
+        assert!((bandwidth - 3.868_27).abs() < 1e-4);
+        let smoothed = smoothed_sample(&values, bandwidth, &mut rng).unwrap();
+        assert!(smoothed > 4.0 - 6.0 * bandwidth && smoothed < 15.0 + 6.0 * bandwidth);
+    }
+
+    #[test]
+    fn test_bootstrap_ci() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let values = [10, 12, 9, 11, 10, 13, 8, 10, 11, 9];
+        let (lower, upper) = bootstrap_ci::<_, _, 16, 500>(
+            &values,
+            |v: &mut [i32]| Statistic::Mean.compute(v),
+            0.9,
+            &mut rng,
+        )
+        .unwrap();
+        assert!(lower < 10.3 && 10.3 < upper);
+        assert!(upper - lower < 3.0);
+        // constant values have no uncertainty
+        let ci = bootstrap_ci::<_, _, 4, 50>(
+            &[3, 3, 3],
+            |v: &mut [i32]| Statistic::Median.compute(v),
+            0.95,
+            &mut rng,
+        );
+        assert_eq!(ci, Some((3.0, 3.0)));
+        let too_many = bootstrap_ci::<_, _, 2, 10>(&[1, 2, 3], |_: &mut [i32]| 0.0, 0.9, &mut rng);
+        assert_eq!(too_many, None);
+    }
+}
@@ -0,0 +1,178 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::feed::Push;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReservoirAlgorithm {
+    // one random number for every value
+    R,
+    // computes how many values to skip, O(K * (1 + ln(n / K))) random numbers for the whole stream
+    L,
+}
+
+// Uniform random sample of up to K values from the whole stream since start, unlike rolling windows it never
+// forgets old values completely. Every value seen so far is in the sample with the same probability K / seen.
+// Feed it from bytes with ByteFeed<Reservoir>, next to RollingStats use RollingStats::write_through so both see
+// the same decoded values.
+pub struct Reservoir<R: Rng, const K: usize> {
+    samples: [i32; K],
+    len: usize,
+    seen: u64,
+    rng: R,
+    algorithm: ReservoirAlgorithm,
+    // Algorithm L: index of the next value which replaces a sample and its running weight
+    next: u64,
+    weight: f64,
+}
+
+impl<const K: usize> Reservoir<StdRng, K> {
+    // reproducible sample, e.g. for audits which have to be repeated
+    pub fn seeded(seed: u64, algorithm: ReservoirAlgorithm) -> Self {
+        Self::new(StdRng::seed_from_u64(seed), algorithm)
+    }
+}
+
+impl<R: Rng, const K: usize> Reservoir<R, K> {
+    pub fn new(rng: R, algorithm: ReservoirAlgorithm) -> Self {
+        Self {
+            samples: [0; K],
+            len: 0,
+            seen: 0,
+            rng,
+            algorithm,
+            next: 0,
+            weight: 1.0,
+        }
+    }
+
+    pub fn push_value(&mut self, value: i32) {
+        let index = self.seen;
+        self.seen += 1;
+        if K == 0 {
+            return;
+        }
+        if self.len < K {
+            self.samples[self.len] = value;
+            self.len += 1;
+            if self.len == K {
+                self.weight = self.random_weight();
+                self.next = index + self.skip() + 1;
+            }
+            return;
+        }
+        match self.algorithm {
+            ReservoirAlgorithm::R => {
+                let slot = self.rng.gen_range(0..=index);
+                if let Some(sample) = self.samples.get_mut(slot as usize) {
+                    *sample = value;
+                }
+            }
+            ReservoirAlgorithm::L => {
+                if index == self.next {
+                    let slot = self.rng.gen_range(0..K);
+                    self.samples[slot] = value;
+                    self.weight *= self.random_weight();
+                    self.next = index + self.skip() + 1;
+                }
+            }
+        }
+    }
+
+    // u^(1/K) for uniform u in (0, 1)
+    fn random_weight(&mut self) -> f64 {
+        let u = self.rng.gen::<f64>().max(f64::MIN_POSITIVE);
+        (u.ln() / K as f64).exp()
+    }
+
+    // how many values are skipped before the next replacement, geometric with success probability weight
+    fn skip(&mut self) -> u64 {
+        let u = self.rng.gen::<f64>().max(f64::MIN_POSITIVE);
+        let skip = (u.ln() / (1.0 - self.weight).ln()).floor();
+        if skip.is_finite() {
+            skip as u64
+        } else {
+            0
+        }
+    }
+
+    // sampled values, in no particular order
+    pub fn samples(&self) -> &[i32] {
+        &self.samples[..self.len]
+    }
+
+    // how many values were pushed since start
+    pub fn seen(&self) -> u64 {
+        self.seen
+    }
+
+    pub fn capacity(&self) -> usize {
+        K
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn algorithm(&self) -> ReservoirAlgorithm {
+        self.algorithm
+    }
+}
+
+impl<R: Rng, const K: usize> Push for Reservoir<R, K> {
+    fn push(&mut self, value: i32) {
+        self.push_value(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feed::ByteFeed;
+
+    #[test]
+    fn test_keeps_first_values_until_full() {
+        let mut feed = ByteFeed::new(Reservoir::<_, 4>::seeded(1, ReservoirAlgorithm::R));
+        feed.write_no_std(&[0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3]);
+        assert_eq!(feed.inner().samples(), &[1, 2, 3]);
+        for value in 4..100 {
+            feed.inner_mut().push_value(value);
+        }
+        let reservoir = feed.inner();
+        assert_eq!((reservoir.len(), reservoir.seen()), (4, 99));
+        assert!(reservoir.samples().iter().all(|v| (1..100).contains(v)));
+    }
+
+    #[test]
+    fn test_seeded_is_reproducible() {
+        for algorithm in [ReservoirAlgorithm::R, ReservoirAlgorithm::L] {
+            let mut a = Reservoir::<_, 8>::seeded(7, algorithm);
+            let mut b = Reservoir::<_, 8>::seeded(7, algorithm);
+            for value in 0..1_000 {
+                a.push_value(value);
+                b.push_value(value);
+            }
+            assert_eq!(a.samples(), b.samples());
+        }
+    }
+
+    #[test]
+    fn test_uniform_inclusion() {
+        for algorithm in [ReservoirAlgorithm::R, ReservoirAlgorithm::L] {
+            let mut counts = [0u32; 5];
+            for seed in 0..2_000 {
+                let mut reservoir = Reservoir::<_, 1>::seeded(seed, algorithm);
+                for value in 0..5 {
+                    reservoir.push_value(value);
+                }
+                counts[reservoir.samples()[0] as usize] += 1;
+            }
+            // 400 expected for every value
+            assert!(counts.iter().all(|c| (320..480).contains(c)));
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use core::default::Default;
+use core::marker::Copy;
+
+use crate::byte_converter::LOG;
+use crate::logs::Logger;
+
+// Fixed capacity circular buffer used as a rolling window. Same idea as FixedArray, data lives on the stack,
+// but when the window is full the oldest element is overwritten and handed back to the caller, so
+// incremental statistics can remove it from their running sums.
+pub struct RingBuffer<T, const N: usize> {
+    data: [T; N],
+    // position of the oldest element in data
+    head: usize,
+    counter: usize,
+    // user defined window size, never bigger than N
+    window_size: usize,
+}
+
+impl<T: Default + Copy, const N: usize> RingBuffer<T, N> {
+    pub fn new(window_size: usize) -> Self {
+        let window_size = if window_size == 0 || window_size > N {
+            LOG.warn("window_size has to be in 1..=N range. Using full capacity of the buffer");
+            N
+        } else {
+            window_size
+        };
+        Self {
+            data: [T::default(); N],
+            head: 0,
+            counter: 0,
+            window_size,
+        }
+    }
+
+    // append to the end of the window. Returns evicted oldest element when the window was full.
+    pub fn push(&mut self, element: T) -> Option<T> {
+        // only possible for N == 0, nothing can be stored
+        if self.window_size == 0 {
+            return None;
+        }
+        // indexes below are always taken modulo window_size <= N so they can`t panic
+        if self.counter < self.window_size {
+            self.data[(self.head + self.counter) % self.window_size] = element;
+            self.counter += 1;
+            None
+        } else {
+            let evicted = self.data[self.head];
+            self.data[self.head] = element;
+            self.head = (self.head + 1) % self.window_size;
+            Some(evicted)
+        }
+    }
+
+    // place element at index by age (0 is the oldest), newer elements move one step. When the window is full the
+    // oldest one of the window and the element is dropped and returned: the oldest element is evicted and the
+    // element lands one step older, or the element itself when it is older than all of them (index 0).
+    pub fn insert(&mut self, index: usize, element: T) -> Option<T> {
+        if self.window_size == 0 || (index == 0 && self.is_full()) {
+            return Some(element);
+        }
+        let (evicted, index) = if self.is_full() {
+            (self.pop_oldest(), index - 1)
+        } else {
+            (None, index)
+        };
+        self.push(element);
+        // new element is the newest one, move it back to index
+        let mut position = self.counter - 1;
+        while position > index {
+            let newer = (self.head + position) % self.window_size;
+            let older = (self.head + position - 1) % self.window_size;
+            self.data.swap(newer, older);
+            position -= 1;
+        }
+        evicted
+    }
+
+    // remove and return the oldest element
+    pub fn pop_oldest(&mut self) -> Option<T> {
+        if self.counter == 0 {
+            return None;
+        }
+        let oldest = self.data[self.head];
+        self.head = (self.head + 1) % self.window_size;
+        self.counter -= 1;
+        Some(oldest)
+    }
+
+    // remove and return the newest element
+    pub fn pop_newest(&mut self) -> Option<T> {
+        let last = self.counter.checked_sub(1)?;
+        let newest = self.get(last).copied();
+        self.counter = last;
+        newest
+    }
+
+    // element by age, 0 is the oldest one
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index < self.counter {
+            self.data.get((self.head + index) % self.window_size)
+        } else {
+            None
+        }
+    }
+
+    pub fn oldest(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    pub fn newest(&self) -> Option<&T> {
+        self.counter.checked_sub(1).and_then(|last| self.get(last))
+    }
+
+    // iterate from the oldest to the newest element
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        (0..self.counter).filter_map(move |i| self.get(i))
+    }
+
+    pub fn clear(&mut self) {
+        self.head = 0;
+        self.counter = 0;
+    }
+
+    pub fn len(&self) -> usize {
+        self.counter
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counter == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.counter == self.window_size
+    }
+
+    pub fn window_size(&self) -> usize {
+        self.window_size
+    }
+}
@@ -0,0 +1,287 @@
+use crate::byte_converter::LOG;
+use crate::change_point::Direction;
+use crate::feed::Push;
+use crate::logs::Logger;
+use crate::ring_buffer::RingBuffer;
+use crate::sums::ExactSums;
+
+// Western Electric rules, sigma is the standard deviation of the chart limits
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpcRule {
+    // one value beyond 3 sigma
+    Beyond3Sigma,
+    // 2 of the last 3 values beyond 2 sigma on the same side
+    TwoOfThreeBeyond2Sigma,
+    // 4 of the last 5 values beyond 1 sigma on the same side
+    FourOfFiveBeyond1Sigma,
+    // 8 values in a row on the same side of the center line
+    EightOnOneSide,
+    // 6 values in a row steadily increasing or decreasing
+    SixTrending,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpcViolation {
+    pub rule: SpcRule,
+    // value which completed the pattern
+    pub value: i32,
+    // index of the value in the stream, counted from 0
+    pub position: u64,
+    // side of the center line, or direction of the trend
+    pub direction: Direction,
+}
+
+// recent values needed by the longest rule
+const HISTORY: usize = 8;
+
+// Shewhart control chart with Western Electric rules. Every value is evaluated against center line and sigma of
+// the last window_size values before it is pushed (live limits, nothing is evaluated until the window is full),
+// or against limits frozen from a known good period. Each rule which matches is handed to on_violation, so a lasting
+// violation is reported on every value. Rules look at the values evaluated since the limits changed.
+pub struct ControlChart<F: FnMut(&SpcViolation), const N: usize> {
+    window: RingBuffer<i32, N>,
+    sums: ExactSums,
+    // (center, sigma) when limits are frozen
+    frozen: Option<(f64, f64)>,
+    // (value, z-score) of the recently evaluated values
+    history: RingBuffer<(i32, f32), HISTORY>,
+    position: u64,
+    violations: u64,
+    on_violation: F,
+}
+
+impl<F: FnMut(&SpcViolation), const N: usize> ControlChart<F, N> {
+    pub fn new(window_size: usize, on_violation: F) -> Self {
+        Self {
+            window: RingBuffer::new(window_size),
+            sums: ExactSums::new(),
+            frozen: None,
+            history: RingBuffer::new(HISTORY),
+            position: 0,
+            violations: 0,
+            on_violation,
+        }
+    }
+
+    // freeze limits at the current window, returns false when the window is not full or constant
+    pub fn freeze(&mut self) -> bool {
+        match self.live_limits() {
+            Some(limits) => {
+                self.frozen = Some(limits);
+                self.history.clear();
+                true
+            }
+            None => {
+                LOG.warn("limits can be frozen only from full window which is not constant");
+                false
+            }
+        }
+    }
+
+    // freeze limits at known center line and sigma, e.g. from a previous capability study
+    pub fn freeze_at(&mut self, center: f32, sigma: f32) {
+        if !(sigma.is_finite() && sigma > 0.0 && center.is_finite()) {
+            LOG.warn("sigma has to be positive, limits are not changed");
+            return;
+        }
+        self.frozen = Some((center as f64, sigma as f64));
+        self.history.clear();
+    }
+
+    // evaluate against the live window again
+    pub fn unfreeze(&mut self) {
+        self.frozen = None;
+        self.history.clear();
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.frozen.is_some()
+    }
+
+    // (center line, sigma) used for the next value, None while live window is not full or constant
+    pub fn limits(&self) -> Option<(f32, f32)> {
+        self.frozen
+            .or_else(|| self.live_limits())
+            .map(|(center, sigma)| (center as f32, sigma as f32))
+    }
+
+    // how many violations were reported so far
+    pub fn violations(&self) -> u64 {
+        self.violations
+    }
+
+    fn live_limits(&self) -> Option<(f64, f64)> {
+        if !self.window.is_full() {
+            return None;
+        }
+        let (center, variance) = self.sums.mean().zip(self.sums.population_variance())?;
+        let sigma = variance.sqrt();
+        if sigma == 0.0 {
+            return None;
+        }
+        Some((center, sigma))
+    }
+
+    // evaluate rules for value and push it into the window, returns its z-score if it was evaluated
+    pub fn push_value(&mut self, value: i32) -> Option<f32> {
+        let position = self.position;
+        self.position += 1;
+
+        let score = self
+            .frozen
+            .or_else(|| self.live_limits())
+            .map(|(center, sigma)| ((value as f64 - center) / sigma) as f32);
+        if let Some(z) = score {
+            self.history.push((value, z));
+            self.check_rules(value, position);
+        }
+
+        if let Some(evicted) = self.window.push(value) {
+            self.sums.remove(evicted);
+        }
+        self.sums.add(value);
+        score
+    }
+
+    fn check_rules(&mut self, value: i32, position: u64) {
+        let z = match self.history.newest() {
+            Some((_, z)) => *z,
+            None => return,
+        };
+        let direction = if z >= 0.0 {
+            Direction::Up
+        } else {
+            Direction::Down
+        };
+        let side = if z >= 0.0 { 1.0 } else { -1.0 };
+
+        if z.abs() > 3.0 {
+            self.report(SpcRule::Beyond3Sigma, value, position, direction);
+        }
+        // newest value has to be one of the values beyond the limit, so the pattern is reported when it forms
+        if side * z > 2.0 && self.count_beyond(3, side, 2.0) >= 2 {
+            self.report(SpcRule::TwoOfThreeBeyond2Sigma, value, position, direction);
+        }
+        if side * z > 1.0 && self.count_beyond(5, side, 1.0) >= 4 {
+            self.report(SpcRule::FourOfFiveBeyond1Sigma, value, position, direction);
+        }
+        if self.history.len() >= 8 && self.count_beyond(8, side, 0.0) == 8 {
+            self.report(SpcRule::EightOnOneSide, value, position, direction);
+        }
+        if let Some(direction) = self.trend_of_last(6) {
+            self.report(SpcRule::SixTrending, value, position, direction);
+        }
+    }
+
+    // how many of the last `last` values are more than limit sigmas away on the side (1 above, -1 below)
+    fn count_beyond(&self, last: usize, side: f32, limit: f32) -> usize {
+        let skip = self.history.len().saturating_sub(last);
+        self.history
+            .iter()
+            .skip(skip)
+            .filter(|(_, z)| side * z > limit)
+            .count()
+    }
+
+    // direction when the last `last` values are strictly monotonic
+    fn trend_of_last(&self, last: usize) -> Option<Direction> {
+        if self.history.len() < last {
+            return None;
+        }
+        let skip = self.history.len() - last;
+        let values = || self.history.iter().skip(skip).map(|(value, _)| *value);
+        let pairs = || values().zip(values().skip(1));
+        if pairs().all(|(a, b)| b > a) {
+            Some(Direction::Up)
+        } else if pairs().all(|(a, b)| b < a) {
+            Some(Direction::Down)
+        } else {
+            None
+        }
+    }
+
+    fn report(&mut self, rule: SpcRule, value: i32, position: u64, direction: Direction) {
+        self.violations += 1;
+        (self.on_violation)(&SpcViolation {
+            rule,
+            value,
+            position,
+            direction,
+        });
+    }
+}
+
+impl<F: FnMut(&SpcViolation), const N: usize> Push for ControlChart<F, N> {
+    fn push(&mut self, value: i32) {
+        self.push_value(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(values: &[i32]) -> ([Option<(SpcRule, u64)>; 8], usize) {
+        let mut fired = [None; 8];
+        let mut count = 0;
+        {
+            let mut chart = ControlChart::<_, 4>::new(4, |v: &SpcViolation| {
+                if count < fired.len() {
+                    fired[count] = Some((v.rule, v.position));
+                }
+                count += 1;
+            });
+            chart.freeze_at(0.0, 10.0);
+            for value in values {
+                chart.push_value(*value);
+            }
+        }
+        (fired, count)
+    }
+
+    #[test]
+    fn test_zone_rules() {
+        let (fired, count) = run(&[31]);
+        assert_eq!((fired[0], count), (Some((SpcRule::Beyond3Sigma, 0)), 1));
+
+        let (fired, count) = run(&[-25, 5, -21]);
+        assert_eq!(
+            (fired[0], count),
+            (Some((SpcRule::TwoOfThreeBeyond2Sigma, 2)), 1)
+        );
+
+        let (fired, count) = run(&[12, 15, 0, 11, 14]);
+        assert_eq!(
+            (fired[0], count),
+            (Some((SpcRule::FourOfFiveBeyond1Sigma, 4)), 1)
+        );
+
+        let (fired, count) = run(&[1, 2, 1, 3, 1, 2, 1, 5]);
+        assert_eq!((fired[0], count), (Some((SpcRule::EightOnOneSide, 7)), 1));
+    }
+
+    #[test]
+    fn test_trend() {
+        let (fired, count) = run(&[5, -9, -7, -5, -3, -1, 1]);
+        assert_eq!((fired[0], count), (Some((SpcRule::SixTrending, 6)), 1));
+    }
+
+    #[test]
+    fn test_live_limits() {
+        let mut count = 0;
+        {
+            let mut chart = ControlChart::<_, 4>::new(4, |v: &SpcViolation| {
+                assert_eq!(v.direction, Direction::Down);
+                count += 1;
+            });
+            for value in [9, 11, 9, 11] {
+                assert_eq!(chart.push_value(value), None);
+            }
+            assert_eq!(chart.limits(), Some((10.0, 1.0)));
+            assert_eq!(chart.push_value(6), Some(-4.0));
+            assert!(chart.freeze());
+            assert!(chart.is_frozen());
+        }
+        assert_eq!(count, 1);
+    }
+}
@@ -0,0 +1,102 @@
+// Exact running sums of i32 values in a window. Values can be added and removed in any order, sums are integers
+// so evicting can`t drift and n * sum(x^2) - sum(x)^2 is never negative.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct ExactSums {
+    count: u64,
+    sum: i64,
+    sum_squares: i128,
+}
+
+impl ExactSums {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn from_values(values: &[i32]) -> Self {
+        let mut sums = Self::new();
+        for value in values {
+            sums.add(*value);
+        }
+        sums
+    }
+
+    pub(crate) fn add(&mut self, value: i32) {
+        self.count += 1;
+        self.sum += value as i64;
+        self.sum_squares += value as i128 * value as i128;
+    }
+
+    // value has to be one which was added before
+    pub(crate) fn remove(&mut self, value: i32) {
+        self.count -= 1;
+        self.sum -= value as i64;
+        self.sum_squares -= value as i128 * value as i128;
+    }
+
+    pub(crate) fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    pub(crate) fn sum(&self) -> i64 {
+        self.sum
+    }
+
+    // None for no values
+    pub(crate) fn mean(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.sum as f64 / self.count as f64)
+    }
+
+    // n * sum(x^2) - sum(x)^2, i.e. n^2 times the population variance
+    pub(crate) fn scaled_variance(&self) -> i128 {
+        self.count as i128 * self.sum_squares - self.sum as i128 * self.sum as i128
+    }
+
+    // divided by n, None for no values
+    pub(crate) fn population_variance(&self) -> Option<f64> {
+        let n = self.count as f64;
+        (self.count > 0).then(|| self.scaled_variance() as f64 / (n * n))
+    }
+
+    // divided by n - 1, None for less than 2 values
+    pub(crate) fn sample_variance(&self) -> Option<f64> {
+        let n = self.count as f64;
+        (self.count > 1).then(|| self.scaled_variance() as f64 / (n * (n - 1.0)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_variance_is_exact() {
+        let mut sums = ExactSums::new();
+        for value in [i32::MAX, i32::MAX, i32::MAX] {
+            sums.add(value);
+        }
+        assert_eq!(sums.count(), 3);
+        assert_eq!(sums.population_variance(), Some(0.0));
+        sums.add(i32::MIN);
+        assert_eq!(
+            sums.mean(),
+            Some((3.0 * i32::MAX as f64 + i32::MIN as f64) / 4.0)
+        );
+        assert!(sums.population_variance().unwrap() > 0.0);
+        sums.remove(i32::MAX);
+        sums.remove(i32::MIN);
+        assert_eq!(sums.sum(), 2 * i32::MAX as i64);
+        assert_eq!(sums.mean(), Some(i32::MAX as f64));
+        assert_eq!(sums.population_variance(), Some(0.0));
+        assert_eq!(
+            (
+                ExactSums::new().mean(),
+                ExactSums::new().population_variance()
+            ),
+            (None, None)
+        );
+    }
+}
@@ -0,0 +1,243 @@
+use core::f64::consts::PI;
+
+use crate::byte_converter::LOG;
+use crate::feed::Push;
+use crate::logs::Logger;
+use crate::order_stats::QuantileSummary;
+
+// Approximate quantiles of the whole stream with constant memory (merging t-digest).
+// Values are collected in a buffer of C values and from time to time merged into at most C centroids
+// (mean, weight). Centroids are small near both tails and big around the median, controlled by the k1 scale
+// function with compression C / 2.
+//
+// Error bound: with compression C / 2 one centroid around quantile q covers at most 4 * PI * sqrt(q * (1 - q)) / C
+// of all values and the answer is interpolated inside it, so the rank error is about 2 * PI * sqrt(q * (1 - q)) / C
+// or smaller. For C = 100 that is at most ~3 % of rank around the median and much less in the tails. Min and max
+// are exact.
+pub struct TDigest<const C: usize> {
+    // (mean, weight) sorted by mean
+    centroids: [(f64, f64); C],
+    centroids_len: usize,
+    // values not merged yet
+    buffer: [i32; C],
+    buffer_len: usize,
+    count: u64,
+    min: i32,
+    max: i32,
+}
+
+impl<const C: usize> TDigest<C> {
+    pub fn new() -> Self {
+        if C < 2 {
+            LOG.warn("t-digest needs at least 2 centroids, all values will be merged into one");
+        }
+        Self {
+            centroids: [(0.0, 0.0); C],
+            centroids_len: 0,
+            buffer: [0; C],
+            buffer_len: 0,
+            count: 0,
+            min: i32::MAX,
+            max: i32::MIN,
+        }
+    }
+
+    pub fn push_value(&mut self, value: i32) {
+        if self.buffer_len == C {
+            self.merge();
+        }
+        // merge made space in buffer, for C == 0 value is only counted
+        if let Some(slot) = self.buffer.get_mut(self.buffer_len) {
+            *slot = value;
+            self.buffer_len += 1;
+        }
+        self.count += 1;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    pub fn min(&self) -> Option<i32> {
+        (self.count > 0).then_some(self.min)
+    }
+
+    pub fn max(&self) -> Option<i32> {
+        (self.count > 0).then_some(self.max)
+    }
+
+    // k1 scale function, one centroid may span at most 1 unit of k
+    fn scale(&self, q: f64) -> f64 {
+        let compression = C as f64 / 2.0;
+        compression / (2.0 * PI) * (2.0 * q.clamp(0.0, 1.0) - 1.0).asin()
+    }
+
+    // merge buffered values into centroids
+    fn merge(&mut self) {
+        if self.buffer_len == 0 {
+            return;
+        }
+        self.buffer[..self.buffer_len].sort_unstable();
+
+        let total: f64 = self.centroids[..self.centroids_len]
+            .iter()
+            .map(|(_, w)| *w)
+            .sum::<f64>()
+            + self.buffer_len as f64;
+
+        let mut merged = [(0.0, 0.0); C];
+        let mut merged_len = 0;
+        // weight of all finished centroids
+        let mut weight_before = 0.0;
+        let mut current: Option<(f64, f64)> = None;
+
+        let (mut c, mut b) = (0, 0);
+        while c < self.centroids_len || b < self.buffer_len {
+            // take smaller of the next centroid and the next buffered value
+            let next = if b >= self.buffer_len
+                || (c < self.centroids_len && self.centroids[c].0 <= self.buffer[b] as f64)
+            {
+                c += 1;
+                self.centroids[c - 1]
+            } else {
+                b += 1;
+                (self.buffer[b - 1] as f64, 1.0)
+            };
+
+            current = match current {
+                None => Some(next),
+                Some((mean, weight)) => {
+                    let k_left = self.scale(weight_before / total);
+                    let k_right = self.scale((weight_before + weight + next.1) / total);
+                    // last slot is used for everything left, so centroids can never overflow
+                    if k_right - k_left <= 1.0 || merged_len + 1 >= C {
+                        let new_weight = weight + next.1;
+                        Some((mean + (next.0 - mean) * next.1 / new_weight, new_weight))
+                    } else {
+                        merged[merged_len] = (mean, weight);
+                        merged_len += 1;
+                        weight_before += weight;
+                        Some(next)
+                    }
+                }
+            };
+        }
+        if let Some(last) = current {
+            merged[merged_len] = last;
+            merged_len += 1;
+        }
+
+        self.centroids = merged;
+        self.centroids_len = merged_len;
+        self.buffer_len = 0;
+    }
+
+    // number of centroids after merging, memory use doesn`t depend on it
+    pub fn centroids(&mut self) -> usize {
+        self.merge();
+        self.centroids_len
+    }
+
+    fn quantile_value(&mut self, q: f32) -> Option<f32> {
+        if self.count == 0 {
+            return None;
+        }
+        if !(0.0..=1.0).contains(&q) {
+            LOG.warn("quantile has to be in 0..=1 range");
+            return None;
+        }
+        self.merge();
+        let centroids = &self.centroids[..self.centroids_len];
+        let total = self.count as f64;
+        let index = q as f64 * total;
+        let (min, max) = (self.min as f64, self.max as f64);
+
+        // interpolate between centers of centroids, each centroid center sits at the middle of its weight.
+        // Before the first and after the last center we interpolate towards exact min and max.
+        let mut previous = (min, 0.0);
+        let mut weight_before = 0.0;
+        for (mean, weight) in centroids {
+            let center = weight_before + weight / 2.0;
+            if index <= center {
+                let (prev_mean, prev_center) = previous;
+                let fraction = if center > prev_center {
+                    (index - prev_center) / (center - prev_center)
+                } else {
+                    1.0
+                };
+                return Some((prev_mean + (mean - prev_mean) * fraction) as f32);
+            }
+            previous = (*mean, center);
+            weight_before += weight;
+        }
+        let (prev_mean, prev_center) = previous;
+        let fraction = if total > prev_center {
+            (index - prev_center) / (total - prev_center)
+        } else {
+            1.0
+        };
+        Some((prev_mean + (max - prev_mean) * fraction) as f32)
+    }
+}
+
+impl<const C: usize> Default for TDigest<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const C: usize> Push for TDigest<C> {
+    fn push(&mut self, value: i32) {
+        self.push_value(value);
+    }
+}
+
+impl<const C: usize> QuantileSummary for TDigest<C> {
+    fn quantile(&mut self, q: f32) -> Option<f32> {
+        self.quantile_value(q)
+    }
+
+    fn count(&self) -> u64 {
+        self.count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::order_stats::RollingQuantiles;
+
+    // same queries through the trait for both backends
+    fn median<S: QuantileSummary>(summary: &mut S, values: &[i32]) -> Option<f32> {
+        for value in values {
+            summary.push(*value);
+        }
+        summary.quantile(0.5)
+    }
+
+    #[test]
+    fn test_backends_agree_on_small_input() {
+        let values = [5, 1, 4, 2, 3];
+        assert_eq!(
+            median(&mut RollingQuantiles::<8>::new(8), &values),
+            Some(3.0)
+        );
+        assert_eq!(median(&mut TDigest::<32>::new(), &values), Some(3.0));
+    }
+
+    #[test]
+    fn test_error_bound_on_long_stream() {
+        let mut digest = TDigest::<100>::new();
+        // values 0..100_000 in scrambled order
+        for i in 0..100_000i64 {
+            digest.push_value(((i * 7919) % 100_000) as i32);
+        }
+        assert_eq!(digest.count(), 100_000);
+        assert!(digest.centroids() <= 100);
+        for q in [0.01, 0.1, 0.5, 0.9, 0.99] {
+            let estimate = digest.quantile(q).unwrap() as f64;
+            let bound = 2.0 * PI * ((q * (1.0 - q)) as f64).sqrt() / 100.0 * 100_000.0;
+            assert!((estimate - q as f64 * 100_000.0).abs() <= bound);
+        }
+        assert_eq!(digest.quantile(0.0), Some(0.0));
+        assert_eq!(digest.quantile(1.0), Some(99_999.0));
+    }
+}
@@ -0,0 +1,615 @@
+use rand::thread_rng;
+use rand_distr::{Distribution, Normal};
+
+use crate::byte_converter::LOG;
+use crate::feed::{Evict, Push};
+use crate::logs::Logger;
+use crate::ring_buffer::RingBuffer;
+use crate::sums::ExactSums;
+use crate::trend::{fit_trend, Trend, TREND_CONFIDENCE};
+
+// Source of timestamps for time based windows. Unit of ticks is up to the clock (us, ms, timer ticks...),
+// window duration is given in the same unit.
+pub trait Clock {
+    fn now(&self) -> u64;
+}
+
+// monotonic std clock, ticks are microseconds since the clock was created
+#[cfg(feature = "std")]
+pub struct StdClock {
+    start: std::time::Instant,
+}
+
+#[cfg(feature = "std")]
+impl StdClock {
+    pub fn new() -> Self {
+        Self {
+            start: std::time::Instant::now(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for StdClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Clock for StdClock {
+    fn now(&self) -> u64 {
+        self.start.elapsed().as_micros() as u64
+    }
+}
+
+// tick counter for no_std, firmware advances it e.g. from a timer interrupt or before every push
+#[derive(Default)]
+pub struct TickClock {
+    ticks: u64,
+}
+
+impl TickClock {
+    pub fn new() -> Self {
+        Self { ticks: 0 }
+    }
+
+    pub fn advance(&mut self, ticks: u64) {
+        self.ticks = self.ticks.saturating_add(ticks);
+    }
+
+    pub fn set(&mut self, ticks: u64) {
+        self.ticks = ticks;
+    }
+}
+
+impl Clock for TickClock {
+    fn now(&self) -> u64 {
+        self.ticks
+    }
+}
+
+// Window of values from the last `duration` ticks regardless of the sample rate. Every value carries its
+// timestamp, either taken from the clock or supplied explicitly with push_at. Expired values are evicted on push
+// and on every query. N limits how many values fit into the window, when it is reached the oldest value is
+// evicted even though it is not expired yet. Other rolling statistics run over time with TimeEvicted.
+// Samples may arrive out of order up to allowed lateness (0 by default), they are inserted by their timestamp.
+// Watermark is the newest timestamp minus allowed lateness, older samples and already expired ones are dropped
+// and counted, same as a late sample older than all values of a full window.
+pub struct TimeWindow<C: Clock, const N: usize> {
+    clock: C,
+    duration: u64,
+    allowed_lateness: u64,
+    // newest timestamp seen so far, also after its value expired
+    max_timestamp: Option<u64>,
+    dropped: u64,
+    window: RingBuffer<(u64, i32), N>,
+    // exact sums of values in the window
+    sums: ExactSums,
+    // timestamps in trend sums are relative to origin (the oldest timestamp), so squares fit into i128 even for
+    // us timestamps
+    origin: u64,
+    sum_t: i128,
+    sum_tt: i128,
+    sum_ty: i128,
+}
+
+impl<C: Clock, const N: usize> TimeWindow<C, N> {
+    pub fn new(clock: C, duration: u64) -> Self {
+        let duration = if duration > 0 {
+            duration
+        } else {
+            LOG.warn("duration of time window has to be positive. Using 1 tick");
+            1
+        };
+        Self {
+            clock,
+            duration,
+            allowed_lateness: 0,
+            max_timestamp: None,
+            dropped: 0,
+            window: RingBuffer::new(N),
+            sums: ExactSums::new(),
+            origin: 0,
+            sum_t: 0,
+            sum_tt: 0,
+            sum_ty: 0,
+        }
+    }
+
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    pub fn clock_mut(&mut self) -> &mut C {
+        &mut self.clock
+    }
+
+    pub fn duration(&self) -> u64 {
+        self.duration
+    }
+
+    // how many ticks a sample may be older than the newest one and still be inserted
+    pub fn set_allowed_lateness(&mut self, allowed_lateness: u64) {
+        self.allowed_lateness = allowed_lateness;
+    }
+
+    pub fn allowed_lateness(&self) -> u64 {
+        self.allowed_lateness
+    }
+
+    // newest timestamp minus allowed lateness, None before the first sample
+    pub fn watermark(&self) -> Option<u64> {
+        self.max_timestamp
+            .map(|t| t.saturating_sub(self.allowed_lateness))
+    }
+
+    // samples which arrived after the watermark or already expired
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    // value with current timestamp of the clock
+    pub fn push_value(&mut self, value: i32) {
+        let now = self.clock.now();
+        self.push_at(now, value);
+    }
+
+    // value with explicit timestamp, late samples are accepted up to allowed lateness
+    pub fn push_at(&mut self, timestamp: u64, value: i32) {
+        let newest = match self.max_timestamp {
+            Some(newest) if timestamp < newest => newest,
+            _ => {
+                self.max_timestamp = Some(timestamp);
+                self.evict_expired(timestamp);
+                if let Some((evicted_timestamp, evicted)) = self.window.push((timestamp, value)) {
+                    LOG.warn("time window is full, oldest value is evicted before it expired");
+                    self.update_sums(evicted_timestamp, evicted, -1);
+                }
+                self.update_sums(timestamp, value, 1);
+                self.rebase_to_oldest();
+                return;
+            }
+        };
+
+        let lateness = newest - timestamp;
+        if lateness > self.allowed_lateness || lateness >= self.duration {
+            LOG.warn("sample is older than the watermark or already expired, it is dropped");
+            self.dropped += 1;
+            return;
+        }
+        // after all values with the same or older timestamp
+        let index = self
+            .window
+            .iter()
+            .take_while(|(t, _)| *t <= timestamp)
+            .count();
+        if index == 0 && self.window.is_full() {
+            LOG.warn("time window is full and sample is older than all its values, it is dropped");
+            self.dropped += 1;
+            return;
+        }
+        if timestamp < self.origin {
+            self.rebase(timestamp);
+        }
+        if let Some((evicted_timestamp, evicted)) = self.window.insert(index, (timestamp, value)) {
+            LOG.warn("time window is full, oldest value is evicted before it expired");
+            self.update_sums(evicted_timestamp, evicted, -1);
+        }
+        self.update_sums(timestamp, value, 1);
+        self.rebase_to_oldest();
+    }
+
+    // move origin of trend sums to another timestamp, t is shifted by d = old origin - new origin
+    fn rebase(&mut self, origin: u64) {
+        let d = self.origin as i128 - origin as i128;
+        let n = self.sums.count() as i128;
+        self.sum_ty += d * self.sums.sum() as i128;
+        self.sum_tt += 2 * d * self.sum_t + n * d * d;
+        self.sum_t += n * d;
+        self.origin = origin;
+    }
+
+    // origin follows the oldest value, so t in trend sums never exceeds the span of the window
+    fn rebase_to_oldest(&mut self) {
+        if let Some((oldest, _)) = self.window.oldest() {
+            self.rebase(*oldest);
+        }
+    }
+
+    fn update_sums(&mut self, timestamp: u64, value: i32, sign: i128) {
+        let (t, y) = ((timestamp - self.origin) as i128, value as i128);
+        if sign > 0 {
+            self.sums.add(value);
+        } else {
+            self.sums.remove(value);
+        }
+        self.sum_t += sign * t;
+        self.sum_tt += sign * t * t;
+        self.sum_ty += sign * t * y;
+    }
+
+    // drop values which are older than duration at time now
+    pub fn evict_expired(&mut self, now: u64) {
+        while let Some((timestamp, _)) = self.window.oldest() {
+            if now.saturating_sub(*timestamp) < self.duration {
+                break;
+            }
+            if let Some((timestamp, value)) = self.window.pop_oldest() {
+                self.update_sums(timestamp, value, -1);
+            }
+        }
+        self.rebase_to_oldest();
+    }
+
+    fn evict_now(&mut self) {
+        let now = self.clock.now();
+        self.evict_expired(now);
+    }
+
+    pub fn len(&mut self) -> usize {
+        self.evict_now();
+        self.window.len()
+    }
+
+    pub fn is_empty(&mut self) -> bool {
+        self.len() == 0
+    }
+
+    // (timestamp, value) pairs from the oldest to the newest, expired values are evicted first
+    pub fn iter(&mut self) -> impl Iterator<Item = &(u64, i32)> + '_ {
+        self.evict_now();
+        self.window.iter()
+    }
+
+    // arithmetic mean of values in the time window
+    pub fn mean(&mut self) -> f32 {
+        self.evict_now();
+        self.sums.mean().unwrap_or(0.0) as f32
+    }
+
+    // population standard deviation of values in the time window
+    pub fn std_deviation(&mut self) -> f32 {
+        self.evict_now();
+        match self.sums.population_variance() {
+            Some(variance) => variance.sqrt() as f32,
+            None => {
+                LOG.error("std_deviation can`t be computed from empty time window");
+                0.0
+            }
+        }
+    }
+
+    // least squares trend of values over time, slope is change per tick. None for less than 3 values or
+    // when all values have the same timestamp.
+    pub fn trend(&mut self) -> Option<Trend> {
+        self.trend_with_confidence(TREND_CONFIDENCE)
+    }
+
+    pub fn trend_with_confidence(&mut self, confidence: f32) -> Option<Trend> {
+        self.evict_now();
+        let (n, newest) = match self.window.newest() {
+            Some((timestamp, _)) => (self.window.len() as i128, *timestamp - self.origin),
+            None => return None,
+        };
+        let sxx = n * self.sum_tt - self.sum_t * self.sum_t;
+        let sxy = n * self.sum_ty - self.sum_t * self.sums.sum() as i128;
+        let syy = self.sums.scaled_variance();
+        let n_f = n as f64;
+        fit_trend(
+            n_f,
+            sxx as f64 / n_f,
+            sxy as f64 / n_f,
+            syy as f64 / n_f,
+            self.sums.sum() as f64 / n_f,
+            newest as f64 - self.sum_t as f64 / n_f,
+            confidence,
+        )
+    }
+
+    // sample from normal distribution with mean and standard deviation of the time window
+    pub fn std_distribution(&mut self) -> f32 {
+        let mean = self.mean();
+        let std_dev = self.std_deviation();
+        if self.window.is_empty() {
+            return 0.0;
+        }
+        match Normal::new(mean, std_dev) {
+            Ok(normal_dis) => normal_dis.sample(&mut thread_rng()),
+            Err(_) => {
+                LOG.error("std_deviation is not finite, can`t sample");
+                0.0
+            }
+        }
+    }
+}
+
+impl<C: Clock, const N: usize> Push for TimeWindow<C, N> {
+    fn push(&mut self, value: i32) {
+        self.push_value(value);
+    }
+}
+
+// Any count based rolling statistic (RollingMinMax, RollingQuantiles, RollingHistogram, RollingMoments) over values
+// from the last `duration` ticks. Timestamps are kept next to the statistic and expired values are evicted from it
+// on push and before every access through stats(). Count window of the statistic and N both limit the number of
+// values, the oldest value is evicted when either is full. Statistics keep values in arrival order, so a sample
+// older than the newest one can`t be placed correctly and is dropped and counted, use TimeWindow for late samples.
+pub struct TimeEvicted<C: Clock, S: Evict, const N: usize> {
+    clock: C,
+    duration: u64,
+    timestamps: RingBuffer<u64, N>,
+    stats: S,
+    dropped: u64,
+}
+
+impl<C: Clock, S: Evict, const N: usize> TimeEvicted<C, S, N> {
+    pub fn new(clock: C, duration: u64, stats: S) -> Self {
+        let duration = if duration > 0 {
+            duration
+        } else {
+            LOG.warn("duration of time window has to be positive. Using 1 tick");
+            1
+        };
+        Self {
+            clock,
+            duration,
+            timestamps: RingBuffer::new(N),
+            stats,
+            dropped: 0,
+        }
+    }
+
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    pub fn clock_mut(&mut self) -> &mut C {
+        &mut self.clock
+    }
+
+    pub fn duration(&self) -> u64 {
+        self.duration
+    }
+
+    // samples dropped because they were older than the newest value
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    // value with current timestamp of the clock
+    pub fn push_value(&mut self, value: i32) {
+        let now = self.clock.now();
+        self.push_at(now, value);
+    }
+
+    // value with explicit timestamp, it has to be at least the timestamp of the newest value
+    pub fn push_at(&mut self, timestamp: u64, value: i32) {
+        if matches!(self.timestamps.newest(), Some(newest) if timestamp < *newest) {
+            LOG.warn("timestamp is older than the newest value, sample is dropped");
+            self.dropped += 1;
+            return;
+        }
+        self.evict_expired(timestamp);
+        let len = self.stats.window_len();
+        self.stats.push(value);
+        if self.stats.window_len() <= len {
+            // statistic evicted its oldest value by count
+            self.timestamps.pop_oldest();
+        }
+        if self.timestamps.push(timestamp).is_some() {
+            LOG.warn("time window is full, oldest value is evicted before it expired");
+            self.stats.evict_oldest();
+        }
+    }
+
+    // drop values which are older than duration at time now
+    pub fn evict_expired(&mut self, now: u64) {
+        while let Some(timestamp) = self.timestamps.oldest() {
+            if now.saturating_sub(*timestamp) < self.duration {
+                break;
+            }
+            self.timestamps.pop_oldest();
+            self.stats.evict_oldest();
+        }
+    }
+
+    // statistic over the time window, expired values are evicted first
+    pub fn stats(&mut self) -> &S {
+        let now = self.clock.now();
+        self.evict_expired(now);
+        &self.stats
+    }
+
+    pub fn len(&mut self) -> usize {
+        self.stats().window_len()
+    }
+
+    pub fn is_empty(&mut self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<C: Clock, S: Evict, const N: usize> Push for TimeEvicted<C, S, N> {
+    fn push(&mut self, value: i32) {
+        self.push_value(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::histogram::RollingHistogram;
+    use crate::min_max::RollingMinMax;
+    use crate::moments::RollingMoments;
+    use crate::order_stats::RollingQuantiles;
+
+    #[test]
+    fn test_values_expire_on_push_and_query() {
+        let mut window = TimeWindow::<TickClock, 16>::new(TickClock::new(), 5);
+        window.push_value(10);
+        window.clock_mut().advance(2);
+        window.push_value(20);
+        window.clock_mut().advance(2);
+        window.push_value(30);
+        assert_eq!(window.mean(), 20.0);
+        // value from tick 0 expires at tick 5
+        window.clock_mut().advance(1);
+        assert_eq!(window.mean(), 25.0);
+        assert_eq!(window.std_deviation(), 5.0);
+        window.clock_mut().set(100);
+        assert!(window.is_empty());
+        assert_eq!(window.std_distribution(), 0.0);
+    }
+
+    #[test]
+    fn test_explicit_timestamps() {
+        let mut window = TimeWindow::<TickClock, 4>::new(TickClock::new(), 1_000);
+        window.push_at(0, 1);
+        window.push_at(500, 2);
+        window.push_at(1_200, 3); // value from 0 expired
+        assert_eq!(window.len(), 2);
+        assert_eq!(window.mean(), 2.5);
+        // capacity is the other limit
+        for value in [4, 5, 6] {
+            window.push_at(1_300, value);
+        }
+        assert_eq!(window.len(), 4);
+        assert_eq!(window.mean(), 4.5);
+    }
+
+    #[test]
+    fn test_trend_over_time() {
+        let mut window = TimeWindow::<TickClock, 8>::new(TickClock::new(), 100);
+        window.push_at(1_000, 0);
+        window.push_at(1_010, 5);
+        window.push_at(1_030, 15);
+        let trend = window.trend().unwrap();
+        assert_eq!(trend.slope, 0.5);
+        assert_eq!(trend.level, 15.0);
+        assert!(trend.significant);
+        window.clock_mut().set(1_200);
+        assert_eq!(window.trend(), None);
+    }
+
+    #[test]
+    fn test_late_samples() {
+        let mut window = TimeWindow::<TickClock, 8>::new(TickClock::new(), 1_000);
+        window.push_at(100, 1);
+        // no lateness is allowed by default
+        window.push_at(50, 2);
+        assert_eq!((window.len(), window.dropped()), (1, 1));
+
+        window.set_allowed_lateness(60);
+        window.push_at(50, 2);
+        window.push_at(80, 9);
+        window.push_at(150, 0);
+        window.push_at(20, 9); // behind watermark 90
+        assert_eq!(window.watermark(), Some(90));
+        assert_eq!(window.dropped(), 2);
+        let values: [(u64, i32); 4] = [(50, 2), (80, 9), (100, 1), (150, 0)];
+        assert!(window.iter().eq(values.iter()));
+
+        // late sample expires by its own timestamp
+        window.push_at(1_060, 0);
+        assert_eq!(window.len(), 4);
+        assert_eq!(window.mean(), 2.5);
+    }
+
+    #[test]
+    fn test_trend_with_late_sample() {
+        let mut window = TimeWindow::<TickClock, 8>::new(TickClock::new(), 1_000);
+        window.set_allowed_lateness(100);
+        window.push_at(100, 1);
+        window.push_at(150, 0);
+        // older than origin of trend sums
+        window.push_at(50, 2);
+        let trend = window.trend().unwrap();
+        assert!((trend.slope + 0.02).abs() < 1e-6);
+        assert!(trend.level.abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_late_sample_older_than_full_window() {
+        let mut window = TimeWindow::<TickClock, 3>::new(TickClock::new(), 1_000);
+        window.set_allowed_lateness(100);
+        window.push_at(50, 1);
+        window.push_at(60, 2);
+        window.push_at(70, 3);
+        // would be evicted right away, so it is dropped instead of the oldest value
+        window.push_at(40, 9);
+        assert_eq!(window.dropped(), 1);
+        // newer than the oldest value, which is evicted
+        window.push_at(55, 4);
+        let values: [(u64, i32); 3] = [(55, 4), (60, 2), (70, 3)];
+        assert!(window.iter().eq(values.iter()));
+        assert_eq!(window.mean(), 3.0);
+    }
+
+    #[test]
+    fn test_origin_follows_oldest_value() {
+        let mut window = TimeWindow::<TickClock, 16>::new(TickClock::new(), 10);
+        // window never empties, every push evicts the oldest value
+        for t in 1_000_000u64..1_000_100 {
+            window.push_at(t, (2 * (t - 1_000_000)) as i32);
+        }
+        assert_eq!(window.origin, 1_000_090);
+        window.clock_mut().set(1_000_099);
+        let trend = window.trend().unwrap();
+        assert!((trend.slope - 2.0).abs() < 1e-6);
+        assert!((trend.level - 198.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_count_based_stats_over_time() {
+        let mut min_max =
+            TimeEvicted::<_, _, 16>::new(TickClock::new(), 10, RollingMinMax::<16>::new(16));
+        let mut quantiles =
+            TimeEvicted::<_, _, 16>::new(TickClock::new(), 10, RollingQuantiles::<16>::new(16));
+        for (timestamp, value) in [(0, 9), (4, 1), (8, 5), (12, 3)] {
+            min_max.push_at(timestamp, value);
+            quantiles.push_at(timestamp, value);
+        }
+        // 9 from tick 0 expired at tick 10
+        assert_eq!(min_max.stats().max(), Some(5));
+        assert_eq!(quantiles.stats().sorted(), &[1, 3, 5]);
+        // 1 from tick 4 expires at tick 14 on query
+        min_max.clock_mut().set(14);
+        quantiles.clock_mut().set(14);
+        assert_eq!(min_max.stats().min(), Some(3));
+        assert_eq!(quantiles.stats().median(), Some(4.0));
+        min_max.push_at(11, 7);
+        assert_eq!((min_max.dropped(), min_max.len()), (1, 2));
+    }
+
+    #[test]
+    fn test_count_window_and_capacity_limit_time_window() {
+        let mut histogram = TimeEvicted::<_, _, 8>::new(
+            TickClock::new(),
+            100,
+            RollingHistogram::<4, 8>::new(0, 4, 2),
+        );
+        let mut moments =
+            TimeEvicted::<_, _, 2>::new(TickClock::new(), 100, RollingMoments::<8>::new(8));
+        for (timestamp, value) in [(0, 0), (1, 1), (2, 3)] {
+            histogram.push_at(timestamp, value);
+            moments.push_at(timestamp, value);
+        }
+        assert_eq!(histogram.stats().counts(), &[0, 1, 0, 1]);
+        assert_eq!(moments.stats().mean(), 2.0);
+        // both values from ticks 1 and 2 expire together
+        histogram.evict_expired(102);
+        moments.evict_expired(102);
+        assert!(histogram.is_empty() && moments.is_empty());
+        assert_eq!(moments.stats().variance(), 0.0);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_std_clock() {
+        let mut window = TimeWindow::<StdClock, 4>::new(StdClock::new(), 60_000_000);
+        window.push_value(3);
+        window.push_value(5);
+        assert_eq!(window.mean(), 4.0);
+    }
+}
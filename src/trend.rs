@@ -0,0 +1,172 @@
+use crate::feed::Push;
+use crate::ring_buffer::RingBuffer;
+use crate::special::student_t_quantile;
+
+// confidence used by trend() for the significance flag
+pub const TREND_CONFIDENCE: f32 = 0.95;
+
+// Least squares line of values over sample index (or time for time windows).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Trend {
+    // change of value per sample (or per tick of the clock)
+    pub slope: f32,
+    // value of the line at the newest sample, current level without noise
+    pub level: f32,
+    // standard error of the slope
+    pub std_error: f32,
+    // slope is different from 0 at the requested confidence (two sided Student t-test)
+    pub significant: bool,
+}
+
+// Fit line from centered sums: n values, sxx = sum((x - mean_x)^2), sxy and syy the same way.
+// newest_x is distance of the newest x from mean_x. Needs at least 3 values and x which is not constant.
+pub(crate) fn fit_trend(
+    n: f64,
+    sxx: f64,
+    sxy: f64,
+    syy: f64,
+    mean_y: f64,
+    newest_x: f64,
+    confidence: f32,
+) -> Option<Trend> {
+    if n < 3.0 || sxx <= 0.0 {
+        return None;
+    }
+    let slope = sxy / sxx;
+    // residual sum of squares, rounding can make it slightly negative for a perfect line
+    let sse = (syy - slope * sxy).max(0.0);
+    let std_error = (sse / (n - 2.0) / sxx).sqrt();
+    let significant = if std_error > 0.0 {
+        let critical = student_t_quantile(1.0 - (1.0 - confidence as f64) / 2.0, n - 2.0);
+        (slope / std_error).abs() > critical
+    } else {
+        slope != 0.0
+    };
+    Some(Trend {
+        slope: slope as f32,
+        level: (mean_y + slope * newest_x) as f32,
+        std_error: std_error as f32,
+        significant,
+    })
+}
+
+// Rolling trend of one stream over sample index. Sums of index and value are exact integers updated on every
+// push/evict, so trend() is O(1) and slow drifts hidden in the mean can be alerted on.
+pub struct RollingTrend<const N: usize> {
+    window: RingBuffer<i32, N>,
+    // index of the next value
+    count: u64,
+    sum_y: i64,
+    sum_yy: i128,
+    sum_t: i128,
+    sum_tt: i128,
+    sum_ty: i128,
+}
+
+impl<const N: usize> RollingTrend<N> {
+    pub fn new(window_size: usize) -> Self {
+        Self {
+            window: RingBuffer::new(window_size),
+            count: 0,
+            sum_y: 0,
+            sum_yy: 0,
+            sum_t: 0,
+            sum_tt: 0,
+            sum_ty: 0,
+        }
+    }
+
+    pub fn push_value(&mut self, value: i32) {
+        let index = self.count as i128;
+        if let Some(evicted) = self.window.push(value) {
+            // evicted value has index of the new one minus window size
+            let evicted_index = index - self.window.window_size() as i128;
+            self.update_sums(evicted_index, evicted, -1);
+        }
+        self.update_sums(index, value, 1);
+        self.count += 1;
+    }
+
+    fn update_sums(&mut self, t: i128, y: i32, sign: i128) {
+        let y = y as i128;
+        self.sum_y += (sign * y) as i64;
+        self.sum_yy += sign * y * y;
+        self.sum_t += sign * t;
+        self.sum_tt += sign * t * t;
+        self.sum_ty += sign * t * y;
+    }
+
+    pub fn len(&self) -> usize {
+        self.window.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.window.is_empty()
+    }
+
+    // trend with significance at TREND_CONFIDENCE, None for less than 3 values
+    pub fn trend(&self) -> Option<Trend> {
+        self.trend_with_confidence(TREND_CONFIDENCE)
+    }
+
+    // confidence is from 0 up to 1, e.g. 0.99
+    pub fn trend_with_confidence(&self, confidence: f32) -> Option<Trend> {
+        let n = self.window.len() as i128;
+        if n == 0 {
+            return None;
+        }
+        // n * centered sums are exact, divided by n only at the end
+        let sxx = n * self.sum_tt - self.sum_t * self.sum_t;
+        let sxy = n * self.sum_ty - self.sum_t * self.sum_y as i128;
+        let syy = n * self.sum_yy - self.sum_y as i128 * self.sum_y as i128;
+        let n_f = n as f64;
+        let newest = (self.count - 1) as f64 - self.sum_t as f64 / n_f;
+        fit_trend(
+            n_f,
+            sxx as f64 / n_f,
+            sxy as f64 / n_f,
+            syy as f64 / n_f,
+            self.sum_y as f64 / n_f,
+            newest,
+            confidence,
+        )
+    }
+}
+
+impl<const N: usize> Push for RollingTrend<N> {
+    fn push(&mut self, value: i32) {
+        self.push_value(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_linear_drift() {
+        let mut stats = RollingTrend::<8>::new(4);
+        stats.push_value(1000);
+        for value in [10, 12, 14, 16] {
+            stats.push_value(value);
+        }
+        let trend = stats.trend().unwrap();
+        assert_eq!(trend.slope, 2.0);
+        assert_eq!(trend.level, 16.0);
+        assert_eq!(trend.std_error, 0.0);
+        assert!(trend.significant);
+    }
+
+    #[test]
+    fn test_noise_is_not_significant() {
+        let mut stats = RollingTrend::<8>::new(8);
+        assert_eq!(stats.trend(), None);
+        for value in [5, 7, 4, 6, 5, 7, 4, 6] {
+            stats.push_value(value);
+        }
+        let trend = stats.trend().unwrap();
+        assert!(trend.slope.abs() < 0.1);
+        assert!(trend.std_error > 0.0);
+        assert!(!trend.significant);
+    }
+}
@@ -0,0 +1,207 @@
+use crate::byte_converter::LOG;
+use crate::feed::Push;
+use crate::logs::Logger;
+use crate::ring_buffer::RingBuffer;
+
+// number of axes in one sample (x, y, z)
+pub const AXES: usize = 3;
+
+// Rolling statistics over 3-axis samples (e.g. IMU accelerometer x/y/z).
+// Values are decoded from the byte stream as i32 and grouped into tuples in x, y, z order. Incomplete tuple
+// is kept until the rest of its axes arrive. Byte order is fixed for the stream by the ByteFeed which decodes
+// it, e.g. ByteFeed::with_byte_order(stats, ByteOrder::Little). All sums are updated on push/evict so every
+// statistic is O(1).
+pub struct VectorRollingStats<const N: usize> {
+    window: RingBuffer<[i32; AXES], N>,
+    // axes of a sample which was not completed yet
+    partial: [i32; AXES],
+    partial_len: usize,
+    // per axis sum
+    sum: [i64; AXES],
+    // sum of products of two axes, sum_prod[i][j] = sum(a_i * a_j). It is exact so evicting can`t drift.
+    sum_prod: [[i128; AXES]; AXES],
+    // magnitude is not an integer, so its sum is kept in float
+    sum_magnitude: f64,
+}
+
+impl<const N: usize> VectorRollingStats<N> {
+    pub fn new(window_size: usize) -> Self {
+        Self {
+            window: RingBuffer::new(window_size),
+            partial: [0; AXES],
+            partial_len: 0,
+            sum: [0; AXES],
+            sum_prod: [[0; AXES]; AXES],
+            sum_magnitude: 0.0,
+        }
+    }
+
+    // add whole sample, oldest sample is evicted when the window is full
+    pub fn push_sample(&mut self, sample: [i32; AXES]) {
+        if let Some(evicted) = self.window.push(sample) {
+            self.update_sums(&evicted, -1);
+        }
+        self.update_sums(&sample, 1);
+    }
+
+    fn update_sums(&mut self, sample: &[i32; AXES], sign: i64) {
+        for i in 0..AXES {
+            self.sum[i] += sign * sample[i] as i64;
+            for j in 0..AXES {
+                self.sum_prod[i][j] += (sign * sample[i] as i64 * sample[j] as i64) as i128;
+            }
+        }
+        self.sum_magnitude += sign as f64 * magnitude(sample);
+    }
+
+    pub fn len(&self) -> usize {
+        self.window.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.window.is_empty()
+    }
+
+    // iterate samples from the oldest to the newest
+    pub fn iter(&self) -> impl Iterator<Item = &[i32; AXES]> + '_ {
+        self.window.iter()
+    }
+
+    // per axis arithmetic mean
+    pub fn mean(&self) -> [f32; AXES] {
+        let mut mean = [0.0; AXES];
+        if self.window.is_empty() {
+            return mean;
+        }
+        let n = self.window.len() as f64;
+        for (m, sum) in mean.iter_mut().zip(self.sum.iter()) {
+            *m = (*sum as f64 / n) as f32;
+        }
+        mean
+    }
+
+    // per axis standard deviation
+    pub fn std_deviation(&self) -> [f32; AXES] {
+        let covariance = self.covariance();
+        let mut std_dev = [0.0; AXES];
+        for (i, s) in std_dev.iter_mut().enumerate() {
+            *s = covariance[i][i].sqrt();
+        }
+        std_dev
+    }
+
+    // population covariance matrix between axes, diagonal holds variance of each axis
+    pub fn covariance(&self) -> [[f32; AXES]; AXES] {
+        let mut covariance = [[0.0; AXES]; AXES];
+        if self.window.is_empty() {
+            LOG.error("covariance can`t be computed from empty window");
+            return covariance;
+        }
+        let n = self.window.len() as i128;
+        for (i, row) in covariance.iter_mut().enumerate() {
+            for (j, c) in row.iter_mut().enumerate() {
+                // n * sum(a_i * a_j) - sum(a_i) * sum(a_j) is exact in i128, division is done at the end
+                let numerator = n * self.sum_prod[i][j] - self.sum[i] as i128 * self.sum[j] as i128;
+                *c = (numerator as f64 / (n * n) as f64) as f32;
+            }
+        }
+        covariance
+    }
+
+    // mean of the euclidean length of samples
+    pub fn magnitude_mean(&self) -> f32 {
+        if self.window.is_empty() {
+            return 0.0;
+        }
+        (self.sum_magnitude / self.window.len() as f64) as f32
+    }
+
+    // standard deviation of the euclidean length of samples
+    pub fn magnitude_std_deviation(&self) -> f32 {
+        if self.window.is_empty() {
+            return 0.0;
+        }
+        let n = self.window.len() as f64;
+        // sum of squared magnitudes is the trace of sum_prod
+        let sum_squares = (0..AXES).map(|i| self.sum_prod[i][i] as f64).sum::<f64>();
+        let mean = self.sum_magnitude / n;
+        let variance = sum_squares / n - mean * mean;
+        // rounding of sum_magnitude can make variance slightly negative
+        variance.max(0.0).sqrt() as f32
+    }
+}
+
+fn magnitude(sample: &[i32; AXES]) -> f64 {
+    sample
+        .iter()
+        .map(|a| *a as f64 * *a as f64)
+        .sum::<f64>()
+        .sqrt()
+}
+
+// values from the byte stream are taken as x, y, z, x, y, z...
+impl<const N: usize> Push for VectorRollingStats<N> {
+    fn push(&mut self, value: i32) {
+        self.partial[self.partial_len] = value;
+        self.partial_len += 1;
+        if self.partial_len == AXES {
+            self.partial_len = 0;
+            self.push_sample(self.partial);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feed::{ByteFeed, ByteOrder};
+
+    #[test]
+    fn test_per_axis_stats() {
+        let mut stats = VectorRollingStats::<8>::new(4);
+        stats.push_sample([1, 2, 3]);
+        stats.push_sample([3, 2, 1]);
+        assert_eq!(stats.mean(), [2.0, 2.0, 2.0]);
+        assert_eq!(stats.std_deviation(), [1.0, 0.0, 1.0]);
+        let covariance = stats.covariance();
+        assert_eq!(covariance[0][2], -1.0);
+        assert_eq!(covariance[0][1], 0.0);
+    }
+
+    #[test]
+    fn test_magnitude() {
+        let mut stats = VectorRollingStats::<8>::new(2);
+        stats.push_sample([100, 100, 100]);
+        stats.push_sample([3, 4, 0]);
+        stats.push_sample([0, 0, 7]); // first sample is evicted
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats.magnitude_mean(), 6.0);
+        assert_eq!(stats.magnitude_std_deviation(), 1.0);
+    }
+
+    #[test]
+    fn test_tuple_split_between_writes() {
+        let mut feed = ByteFeed::new(VectorRollingStats::<8>::new(4));
+        // first tuple is complete, from the second one only x and y and half of z arrive
+        feed.write_no_std(&[
+            0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0, 5, 0, 0, 0, 5, 0, 0,
+        ]);
+        assert_eq!(feed.inner().len(), 1);
+        feed.write_no_std(&[0, 5]);
+        assert_eq!(feed.inner().len(), 2);
+        assert_eq!(feed.inner().mean(), [3.0, 3.5, 4.0]);
+    }
+
+    #[test]
+    fn test_little_endian_negative_axes() {
+        let mut feed =
+            ByteFeed::with_byte_order(VectorRollingStats::<8>::new(4), ByteOrder::Little);
+        let mut buf = [0u8; 24];
+        for (chunk, value) in buf.chunks_exact_mut(4).zip([-1, 256, -300, -3, 0, 300]) {
+            chunk.copy_from_slice(&i32::to_le_bytes(value));
+        }
+        feed.write_no_std(&buf);
+        assert_eq!(feed.inner().len(), 2);
+        assert_eq!(feed.inner().mean(), [-2.0, 128.0, 0.0]);
+    }
+}
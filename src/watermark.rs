@@ -0,0 +1,301 @@
+use crate::batch_window::{Accumulator, WindowSummary};
+use crate::byte_converter::LOG;
+use crate::feed::Push;
+use crate::logs::Logger;
+
+// window which already got at least one value
+#[derive(Clone, Copy)]
+struct Slot {
+    start: u64,
+    acc: Accumulator,
+    revision: u32,
+    // first summary was handed out already
+    emitted: bool,
+}
+
+// Tumbling windows by event time which accept samples arriving out of order, e.g. from several radios.
+// Window [k * size, (k + 1) * size) is emitted as soon as any sample with timestamp after its end arrives. It is
+// kept for allowed_lateness more ticks, late samples are still inserted into it and the corrected summary is
+// emitted again with higher revision. Watermark is the newest timestamp minus allowed_lateness, samples for
+// windows ending at or before the watermark are dropped and counted.
+// Up to M windows are kept at once, M should be at least allowed_lateness / size + 2. When all are in use the
+// oldest window is finalized early and set aside in up to M more slots until the watermark passes it, so late
+// samples still correct it with the next revision. Samples for windows which had to be forgotten are dropped.
+pub struct EventTimeWindow<F: FnMut(&WindowSummary), const M: usize> {
+    size: u64,
+    allowed_lateness: u64,
+    // newest timestamp seen so far
+    max_timestamp: Option<u64>,
+    slots: [Option<Slot>; M],
+    // windows finalized before their lateness passed
+    retired: [Option<Slot>; M],
+    // windows ending at or before it were forgotten before their lateness passed
+    forgotten_end: u64,
+    dropped: u64,
+    corrected: u64,
+    samples: u64,
+    on_emit: F,
+}
+
+impl<F: FnMut(&WindowSummary), const M: usize> EventTimeWindow<F, M> {
+    pub fn new(size: u64, allowed_lateness: u64, on_emit: F) -> Self {
+        let size = if size > 0 {
+            size
+        } else {
+            LOG.warn("window size has to be positive. Using 1");
+            1
+        };
+        if allowed_lateness / size + 2 > M as u64 {
+            LOG.warn("M is too small for allowed_lateness, windows will be finalized early");
+        }
+        Self {
+            size,
+            allowed_lateness,
+            max_timestamp: None,
+            slots: [None; M],
+            retired: [None; M],
+            forgotten_end: 0,
+            dropped: 0,
+            corrected: 0,
+            samples: 0,
+            on_emit,
+        }
+    }
+
+    // newest timestamp minus allowed lateness, None before the first sample
+    pub fn watermark(&self) -> Option<u64> {
+        self.max_timestamp
+            .map(|t| t.saturating_sub(self.allowed_lateness))
+    }
+
+    // samples which arrived after the watermark passed their window or the window was forgotten
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    // how many times already emitted summary was emitted again with late samples
+    pub fn corrected(&self) -> u64 {
+        self.corrected
+    }
+
+    pub fn push_at(&mut self, timestamp: u64, value: i32) {
+        let start = timestamp - timestamp % self.size;
+        if matches!(self.watermark(), Some(watermark) if start + self.size <= watermark)
+            || start + self.size <= self.forgotten_end
+        {
+            self.dropped += 1;
+            return;
+        }
+
+        let slot = match find_slot(&self.retired, start) {
+            Some(index) => self.retired[index].as_mut(),
+            None => {
+                let index = match find_slot(&self.slots, start) {
+                    Some(index) => index,
+                    None => self.free_slot(),
+                };
+                self.slots.get_mut(index).map(|slot| {
+                    slot.get_or_insert(Slot {
+                        start,
+                        acc: Accumulator::new(),
+                        revision: 0,
+                        emitted: false,
+                    })
+                })
+            }
+        };
+        if let Some(slot) = slot {
+            slot.acc.add(value);
+            if slot.emitted {
+                // late sample changed summary which was already handed out
+                slot.revision += 1;
+                self.corrected += 1;
+                let mut summary = slot.acc.summary(start, start + self.size);
+                summary.revision = slot.revision;
+                (self.on_emit)(&summary);
+            }
+        }
+        self.advance_to(timestamp);
+    }
+
+    // move event time forward without a sample, emits and finalizes windows which are over
+    pub fn advance_to(&mut self, now: u64) {
+        if self.max_timestamp.is_some_and(|t| t > now) {
+            return;
+        }
+        self.max_timestamp = Some(now);
+        self.emit_while(|start, size| start + size <= now);
+
+        let watermark = now.saturating_sub(self.allowed_lateness);
+        for slot in self.slots.iter_mut().chain(self.retired.iter_mut()) {
+            if matches!(slot, Some(s) if s.start + self.size <= watermark) {
+                *slot = None;
+            }
+        }
+    }
+
+    // emit all windows which were not emitted yet and forget all of them, e.g. at the end of the stream
+    pub fn flush(&mut self) {
+        self.emit_while(|_, _| true);
+        self.slots = [None; M];
+        self.retired = [None; M];
+    }
+
+    // free slot, or the oldest window is finalized and set aside to make space
+    fn free_slot(&mut self) -> usize {
+        if let Some(index) = self.slots.iter().position(|s| s.is_none()) {
+            return index;
+        }
+        LOG.warn("all windows are in use, oldest window is finalized before its lateness passed");
+        let oldest = oldest_slot(&self.slots);
+        if let Some(mut slot) = self.slots.get_mut(oldest).and_then(|slot| slot.take()) {
+            if !slot.emitted {
+                slot.emitted = true;
+                (self.on_emit)(&slot.acc.summary(slot.start, slot.start + self.size));
+            }
+            let index = match self.retired.iter().position(|s| s.is_none()) {
+                Some(index) => index,
+                None => {
+                    LOG.warn("too many finalized windows, oldest one is forgotten and its late samples dropped");
+                    let index = oldest_slot(&self.retired);
+                    if let Some(Some(forgotten)) = self.retired.get(index) {
+                        self.forgotten_end = self.forgotten_end.max(forgotten.start + self.size);
+                    }
+                    index
+                }
+            };
+            if let Some(retired) = self.retired.get_mut(index) {
+                *retired = Some(slot);
+            }
+        }
+        oldest
+    }
+
+    // emit first summary of windows matching the condition, oldest first
+    fn emit_while(&mut self, should_emit: impl Fn(u64, u64) -> bool) {
+        loop {
+            let next = self
+                .slots
+                .iter_mut()
+                .flatten()
+                .filter(|s| !s.emitted && should_emit(s.start, self.size))
+                .min_by_key(|s| s.start);
+            match next {
+                Some(slot) => {
+                    slot.emitted = true;
+                    let summary = slot.acc.summary(slot.start, slot.start + self.size);
+                    (self.on_emit)(&summary);
+                }
+                None => break,
+            }
+        }
+    }
+}
+
+fn find_slot(slots: &[Option<Slot>], start: u64) -> Option<usize> {
+    slots
+        .iter()
+        .position(|s| matches!(s, Some(slot) if slot.start == start))
+}
+
+// index of the window with the smallest start, slots are expected to be all in use
+fn oldest_slot(slots: &[Option<Slot>]) -> usize {
+    slots
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, s)| s.map(|slot| slot.start))
+        .map(|(index, _)| index)
+        .unwrap_or(0)
+}
+
+// through ByteFeed the index of the sample is its timestamp, so samples are never late
+impl<F: FnMut(&WindowSummary), const M: usize> Push for EventTimeWindow<F, M> {
+    fn push(&mut self, value: i32) {
+        self.push_at(self.samples, value);
+        self.samples += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_late_sample_corrects_summary() {
+        let mut emitted = [(0, 0, 0.0, 0); 8];
+        let mut count = 0;
+        let dropped;
+        {
+            let mut window = EventTimeWindow::<_, 4>::new(10, 15, |s: &WindowSummary| {
+                emitted[count] = (s.start, s.count, s.mean, s.revision);
+                count += 1;
+            });
+            window.push_at(1, 2);
+            window.push_at(12, 5); // window [0, 10) is emitted
+            window.push_at(8, 4); // late, but within lateness
+            window.push_at(24, 7); // window [10, 20) is emitted, watermark 9
+            window.push_at(9, 6); // still in lateness
+            window.push_at(30, 1); // watermark 15, window [0, 10) is finalized
+            window.push_at(3, 100); // too late
+            window.flush();
+            dropped = window.dropped();
+            assert_eq!(window.corrected(), 2);
+        }
+        assert_eq!(dropped, 1);
+        assert_eq!(
+            &emitted[..count],
+            &[
+                (0, 1, 2.0, 0),
+                (0, 2, 3.0, 1),
+                (10, 1, 5.0, 0),
+                (0, 3, 4.0, 2),
+                (20, 1, 7.0, 0),
+                (30, 1, 1.0, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_early_finalized_window_keeps_revisions() {
+        let mut emitted = [(0, 0, 0.0, 0); 8];
+        let mut count = 0;
+        {
+            // lateness needs 12 windows, only 2 are kept
+            let mut window = EventTimeWindow::<_, 2>::new(10, 100, |s: &WindowSummary| {
+                emitted[count] = (s.start, s.count, s.mean, s.revision);
+                count += 1;
+            });
+            window.push_at(5, 2);
+            window.push_at(15, 4); // window [0, 10) is emitted
+            window.push_at(25, 6); // window [0, 10) is finalized early, [10, 20) is emitted
+            window.push_at(7, 8); // corrects [0, 10) with all its values
+            window.push_at(8, 2);
+            window.flush();
+            assert_eq!((window.corrected(), window.dropped()), (2, 0));
+        }
+        assert_eq!(
+            &emitted[..count],
+            &[
+                (0, 1, 2.0, 0),
+                (10, 1, 4.0, 0),
+                (0, 2, 5.0, 1),
+                (0, 3, 4.0, 2),
+                (20, 1, 6.0, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_in_order_stream() {
+        let mut count = 0;
+        {
+            let mut window = EventTimeWindow::<_, 2>::new(2, 0, |_: &WindowSummary| count += 1);
+            for value in 0..6 {
+                window.push(value);
+            }
+            assert_eq!(window.watermark(), Some(5));
+            window.flush();
+        }
+        assert_eq!(count, 3);
+    }
+}